@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use andromeda_subscription::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use andromeda_subscription::subscription::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,18 +1,31 @@
 #[cfg(not(feature = "library"))]
 use crate::state::{
-    get_and_increment_next_subscription_id, subscriptions, SubscriptionState, NEXT_SUBSCRIPTION_ID,
+    access_end_key, coupon_code_hash, credit_balance, debit_balance, find_subscriber_subscription,
+    get_and_increment_next_subscription_id, migrate_subscriptions, offerings,
+    read_subscriber_subscriptions, read_subscriptions, subscriptions, Asset, Coupon,
+    CouponDiscount, Offering, OfferingUpdate, Payout, RefundPolicy, RevenueShare,
+    SubscriptionState, SubscriptionStatus, CLAIMED_TRIALS, COUPONS, COUPON_REDEMPTIONS,
+    CREATOR_REVENUE, MEMBERSHIP_NFT, NEXT_SUBSCRIPTION_ID, SUBSCRIBER_BALANCES,
+};
+use std::collections::BTreeMap;
+
+use crate::subscription::{
+    AssetBalance, Cw20HookMsg, Cw721HookMsg, ExecuteMsg, HasAccessResponse, InstantiateMsg,
+    MembershipMetadata, MembershipNftExecuteMsg, MembershipTokenExtension, MigrateMsg, QueryMsg,
+    SimulatePriceResponse,
 };
-use crate::subscription::{Cw20HookMsg, Cw721HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
 
 use cosmwasm_std::{
-    ensure, entry_point, from_json, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
-    Uint128,
+    coins, ensure, entry_point, from_json, to_json_binary, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage, SubMsg, Timestamp,
+    Uint128, WasmMsg,
 };
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, Map, PrefixBound};
 
 use andromeda_std::{
     ado_base::InstantiateMsg as BaseInstantiateMsg,
     ado_contract::ADOContract,
+    amp::{AndrAddr, Recipient},
     common::{
         actions::call_action,
         context::ExecuteContext,
@@ -20,18 +33,31 @@ use andromeda_std::{
             authorize_addresses, execute_authorize_contract, execute_deauthorize_contract,
             AuthorizedAddressesResponse, PermissionAction, SEND_CW20_ACTION, SEND_NFT_ACTION,
         },
-        encode_binary, OrderBy,
+        encode_binary, Funds, OrderBy,
     },
     error::ContractError,
 };
 
-use cw20::Cw20ReceiveMsg;
-use cw721::Cw721ReceiveMsg;
+use cw20::{
+    AllowanceResponse, BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg,
+};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
 
-use cw_utils::{nonpayable, Expiration};
+use cw_utils::{must_pay, nonpayable, one_coin, Expiration};
+use semver::Version;
 
 const MAX_LIMIT: u64 = 30;
 const DEFAULT_LIMIT: u64 = 10;
+const MAX_REVENUE_RECIPIENTS: usize = 10;
+const MAX_REVENUE_WEIGHT: u64 = 1_000_000;
+// Longest time a free trial or a payment for one or more periods can cover, ten years in seconds
+const MAX_PERIOD_SECONDS: u64 = 10 * 365 * 24 * 60 * 60;
+// Longest grace period an offering can have, one year in seconds
+const MAX_GRACE_PERIOD_SECONDS: u64 = 365 * 24 * 60 * 60;
+
+// actions that rates can be configured for
+const SUBSCRIBE_ACTION: &str = "Subscribe";
+const RENEW_ACTION: &str = "Renew";
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:andromeda-subscription";
@@ -72,6 +98,12 @@ pub fn instantiate(
         authorize_addresses(&mut deps, SEND_CW20_ACTION, authorized_cw20_addresses)?;
     }
 
+    // Mint membership tokens from the configured CW721
+    if let Some(membership_nft) = msg.membership_nft {
+        let membership_nft = membership_nft.get_raw_address(&deps.as_ref())?;
+        MEMBERSHIP_NFT.save(deps.storage, &membership_nft)?;
+    }
+
     Ok(inst_resp)
 }
 
@@ -92,6 +124,29 @@ pub fn execute(
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Only contracts upgraded from 0.1.0 hold records in its layout, convert them before
+    // bumping the version
+    let stored = cw2::get_contract_version(deps.storage)?;
+    let stored_version =
+        Version::parse(&stored.version).map_err(|err| ContractError::CustomError {
+            msg: format!(
+                "Invalid stored contract version {}: {}",
+                stored.version, err
+            ),
+        })?;
+    let migrated = if stored_version < Version::new(0, 2, 0) {
+        migrate_subscriptions(deps.storage, msg.legacy_payment_asset)?
+    } else {
+        0
+    };
+
+    Ok(ADOContract::default()
+        .migrate(deps, CONTRACT_NAME, CONTRACT_VERSION)?
+        .add_attribute("migrated_subscriptions", migrated.to_string()))
+}
+
 pub fn handle_execute(mut ctx: ExecuteContext, msg: ExecuteMsg) -> Result<Response, ContractError> {
     let action_response = call_action(
         &mut ctx.deps,
@@ -103,7 +158,110 @@ pub fn handle_execute(mut ctx: ExecuteContext, msg: ExecuteMsg) -> Result<Respon
     let res = match msg {
         ExecuteMsg::ReceiveNft(msg) => handle_receive_cw721(ctx, msg),
         ExecuteMsg::Receive(msg) => handle_receive_cw20(ctx, msg),
-        ExecuteMsg::Cancel { nft_address } => execute_cancel(ctx, nft_address),
+        ExecuteMsg::Subscribe {
+            token_id,
+            nft_address,
+            coupon,
+        } => handle_native_subscribe(ctx, token_id, nft_address, coupon),
+        ExecuteMsg::StartTrial {
+            token_id,
+            nft_address,
+        } => execute_start_trial(ctx, nft_address, token_id),
+        ExecuteMsg::Renew {
+            token_id,
+            nft_address,
+            subscription_id,
+            coupon,
+        } => handle_native_renew(ctx, token_id, nft_address, subscription_id, coupon),
+        ExecuteMsg::Cancel {
+            nft_address,
+            token_id,
+            subscription_id,
+            immediate,
+        } => execute_cancel(ctx, nft_address, token_id, subscription_id, immediate),
+        ExecuteMsg::SetAutoRenew {
+            nft_address,
+            token_id,
+            enabled,
+        } => execute_set_auto_renew(ctx, nft_address, token_id, enabled),
+        ExecuteMsg::ProcessRenewals { limit } => execute_process_renewals(ctx, limit),
+        ExecuteMsg::Deposit {} => handle_native_deposit(ctx),
+        ExecuteMsg::WithdrawBalance { asset, amount } => {
+            execute_withdraw_balance(ctx, asset, amount)
+        }
+        ExecuteMsg::UpdateOffering {
+            nft_address,
+            token_id,
+            payment_amount,
+            duration,
+            metadata,
+            refund_policy,
+            max_periods,
+            multi_period_discount,
+            grace_period_seconds,
+            trial_duration,
+            transferable,
+            revenue_split,
+            payout,
+        } => execute_update_offering(
+            ctx,
+            nft_address,
+            token_id,
+            OfferingUpdate {
+                payment_amount,
+                duration,
+                metadata,
+                refund_policy,
+                max_periods,
+                multi_period_discount,
+                grace_period_seconds,
+                trial_duration,
+                transferable,
+                revenue_split,
+                payout,
+            },
+        ),
+        ExecuteMsg::CreateCoupon {
+            nft_address,
+            token_id,
+            code,
+            discount,
+            max_redemptions,
+            max_redemptions_per_address,
+            valid_from,
+            valid_until,
+        } => execute_create_coupon(
+            ctx,
+            nft_address,
+            token_id,
+            code,
+            Coupon {
+                discount,
+                max_redemptions,
+                max_redemptions_per_address,
+                valid_from,
+                valid_until,
+                redemptions: 0,
+            },
+        ),
+        ExecuteMsg::DeleteCoupon {
+            nft_address,
+            token_id,
+            code,
+        } => execute_delete_coupon(ctx, nft_address, token_id, code),
+        ExecuteMsg::DelistOffering {
+            nft_address,
+            token_id,
+        } => execute_delist_offering(ctx, nft_address, token_id),
+        ExecuteMsg::TransferSubscription {
+            offering,
+            recipient,
+        } => execute_transfer_subscription(ctx, offering, recipient),
+        ExecuteMsg::WithdrawRevenue {
+            asset,
+            amount,
+            recipient,
+        } => execute_withdraw_revenue(ctx, asset, amount, recipient),
         ExecuteMsg::AuthorizeContract {
             action,
             addr,
@@ -134,17 +292,15 @@ pub fn handle_receive_cw20(
     )?;
 
     let ExecuteContext {
-        ref info,
-        ref env,
-        ref mut deps,
-        ..
+        deps, info, env, ..
     } = ctx;
 
     // Ensure the transaction is non-payable
-    nonpayable(info)?;
+    nonpayable(&info)?;
 
     let amount_sent = receive_msg.amount;
     let subscriber = receive_msg.sender.clone();
+    let payment = Asset::Cw20Token(info.sender.to_string());
 
     ensure!(
         !amount_sent.is_zero(),
@@ -157,260 +313,2034 @@ pub fn handle_receive_cw20(
         Cw20HookMsg::Subscribe {
             token_id,
             nft_address,
+            coupon,
+        } => execute_subscribe(
+            deps,
+            &env,
+            Payment {
+                subscriber,
+                asset: payment,
+                amount: amount_sent,
+                coupon,
+            },
+            token_id,
+            nft_address,
+        ),
+        Cw20HookMsg::Renew {
+            token_id,
+            nft_address,
+            subscription_id,
+            coupon,
         } => {
-            // Step 1: Check for open subscription (creator address + empty subscriber)
-            let open_key = (nft_address.clone(), String::new());
-            let open_subscription = subscriptions()
-                .may_load(deps.storage, open_key.clone())?
-                .ok_or(ContractError::CustomError {
-                    msg: format!(
-                        "No subscription offering found for creator address {}.",
-                        nft_address
-                    ),
-                })?;
+            let (nft_address, token_id, _) = subscription_key(
+                deps.storage,
+                &subscriber,
+                nft_address,
+                token_id,
+                subscription_id,
+            )?;
+            execute_renew(
+                deps,
+                &env,
+                Payment {
+                    subscriber,
+                    asset: payment,
+                    amount: amount_sent,
+                    coupon,
+                },
+                token_id,
+                nft_address,
+            )
+        }
+        Cw20HookMsg::Deposit {} => execute_deposit(deps, subscriber, payment, amount_sent),
+    }
+}
 
-            ensure!(
-                !open_subscription.is_active,
-                ContractError::CustomError {
-                    msg: "This subscription is already marked as active.".to_string(),
-                }
-            );
+pub fn handle_native_subscribe(
+    ctx: ExecuteContext,
+    token_id: String,
+    nft_address: String,
+    coupon: Option<String>,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, info, env, ..
+    } = ctx;
 
-            // Step 2: Check for existing subscription for this user (creator address + subscriber)
-            let user_key = (nft_address.clone(), subscriber.clone());
-            if let Some(existing_subscription) =
-                subscriptions().may_load(deps.storage, user_key.clone())?
-            {
-                return Err(ContractError::CustomError {
-                         msg: format!(
-                            "You already have a subscription to {} offering. Please renew (if inactive) or cancel it.", 
-                            existing_subscription.nft_address
-                        ),
-                     }
-                 );
-            }
+    let offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    let denom = accepted_native_denom(&offering.payment_asset)?;
+    let amount_sent = must_pay(&info, &denom)?;
 
-            // Validate the payment amount
-            ensure!(
-                amount_sent == open_subscription.payment_amount,
-                ContractError::InvalidFunds {
-                    msg: format!(
-                        "Invalid payment amount. Expected {}, received {}.",
-                        open_subscription.payment_amount, amount_sent
-                    ),
-                }
-            );
+    execute_subscribe(
+        deps,
+        &env,
+        Payment {
+            subscriber: info.sender.to_string(),
+            asset: Asset::NativeToken(denom),
+            amount: amount_sent,
+            coupon,
+        },
+        token_id,
+        nft_address,
+    )
+}
 
-            let new_subscription = SubscriptionState {
-                subscription_id: get_and_increment_next_subscription_id(deps.storage)?,
-                creator: open_subscription.creator.clone(),
-                subscriber: subscriber.clone(),
-                token_id,
-                nft_address: open_subscription.nft_address.clone(),
-                start_time: Expiration::AtTime(env.block.time),
-                end_time: Expiration::AtTime(
-                    env.block
-                        .time
-                        .plus_seconds(open_subscription.subscription_duration),
+pub fn handle_native_renew(
+    ctx: ExecuteContext,
+    token_id: Option<String>,
+    nft_address: Option<String>,
+    subscription_id: Option<Uint128>,
+    coupon: Option<String>,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, info, env, ..
+    } = ctx;
+
+    let composite_key = subscription_key(
+        deps.storage,
+        info.sender.as_str(),
+        nft_address,
+        token_id,
+        subscription_id,
+    )?;
+    let (nft_address, token_id, _) = composite_key.clone();
+    let subscription = subscriptions()
+        .may_load(deps.storage, composite_key)?
+        .ok_or(ContractError::CustomError {
+            msg: format!(
+                "No subscription found for creator address {} and subscriber {}.",
+                nft_address, info.sender
+            ),
+        })?;
+    let denom = accepted_native_denom(&subscription.payment_asset)?;
+    let amount_sent = must_pay(&info, &denom)?;
+
+    execute_renew(
+        deps,
+        &env,
+        Payment {
+            subscriber: info.sender.to_string(),
+            asset: Asset::NativeToken(denom),
+            amount: amount_sent,
+            coupon,
+        },
+        token_id,
+        nft_address,
+    )
+}
+
+/// Payment sent by a subscriber to subscribe or renew
+struct Payment {
+    subscriber: String,
+    asset: Asset,
+    amount: Uint128,
+    coupon: Option<String>, // Coupon code to redeem for a discount on the price
+}
+
+fn execute_subscribe(
+    deps: DepsMut,
+    env: &Env,
+    payment: Payment,
+    token_id: String,
+    nft_address: String,
+) -> Result<Response, ContractError> {
+    let Payment {
+        subscriber,
+        asset: payment,
+        amount: amount_sent,
+        coupon,
+    } = payment;
+
+    // Step 1: Check for the listed offering (creator address + tier token)
+    let mut offering = load_offering(deps.storage, &nft_address, &token_id)?;
+
+    // Step 2: Check for existing subscription for this user (creator address + tier token + subscriber)
+    let existing_subscription =
+        load_replaceable_subscription(deps.storage, env, &offering, &subscriber)?;
+
+    // Validate the payment asset and amount, discounted by the coupon if one is redeemed
+    ensure_accepted_payment(&offering.payment_asset, &payment)?;
+    let coupon = coupon
+        .map(|code| {
+            load_redeemable_coupon(
+                deps.storage,
+                env,
+                offering.offering_id,
+                &code,
+                Some(&subscriber),
+            )
+        })
+        .transpose()?;
+    let periods = periods_paid_for(
+        offering.payment_amount,
+        offering.max_periods,
+        offering.multi_period_discount,
+        coupon.as_ref().map(|(_, coupon)| &coupon.discount),
+        amount_sent,
+    )?;
+    if let Some((code_hash, coupon)) = coupon {
+        redeem_coupon(
+            deps.storage,
+            offering.offering_id,
+            code_hash,
+            coupon,
+            &subscriber,
+        )?;
+    }
+
+    // Platform fees configured for the action are paid out before crediting the creator
+    let (creator_amount, rates_response) =
+        deduct_rates(deps.as_ref(), SUBSCRIBE_ACTION, &payment, amount_sent)?;
+
+    let mut new_subscription = build_subscription(
+        deps.storage,
+        env,
+        &offering,
+        subscriber.clone(),
+        offering.subscription_duration.saturating_mul(periods),
+    )?;
+    new_subscription.amount_paid = amount_sent;
+    new_subscription.amount_credited = creator_amount;
+    let membership_msgs = save_new_subscription(
+        deps.storage,
+        env,
+        &mut offering,
+        existing_subscription,
+        &mut new_subscription,
+    )?;
+
+    let revenue_msgs = distribute_revenue(deps, &offering, &payment, creator_amount)?;
+
+    Ok(Response::new()
+        .add_messages(membership_msgs)
+        .add_submessages(rates_response.messages)
+        .add_submessages(revenue_msgs)
+        .add_events(rates_response.events)
+        .add_attribute("action", "subscribe")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("creator", new_subscription.creator)
+        .add_attribute("creator address", new_subscription.nft_address)
+        .add_attribute("start_time", new_subscription.start_time.to_string())
+        .add_attribute("end_time", new_subscription.end_time.to_string())
+        .add_attribute(
+            "is_active",
+            new_subscription.status.has_access().to_string(),
+        )
+        .add_attributes(rates_response.attributes))
+}
+
+pub fn execute_start_trial(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, env, info, ..
+    } = ctx;
+
+    nonpayable(&info)?;
+
+    let mut offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    ensure!(
+        offering.trial_duration > 0,
+        ContractError::CustomError {
+            msg: "This offering does not have a free trial.".to_string(),
+        }
+    );
+
+    // Trials are tracked apart from subscriptions so cancelling and resubscribing cannot
+    // claim another one
+    let subscriber = info.sender.to_string();
+    let trial_key = (subscriber.clone(), offering.offering_id.u128());
+    ensure!(
+        !CLAIMED_TRIALS.has(deps.storage, trial_key.clone()),
+        ContractError::CustomError {
+            msg: "The free trial of this offering has already been claimed.".to_string(),
+        }
+    );
+    let existing_subscription =
+        load_replaceable_subscription(deps.storage, &env, &offering, &subscriber)?;
+
+    let mut new_subscription = build_subscription(
+        deps.storage,
+        &env,
+        &offering,
+        subscriber.clone(),
+        offering.trial_duration,
+    )?;
+    new_subscription.status = SubscriptionStatus::Trialing;
+    let membership_msgs = save_new_subscription(
+        deps.storage,
+        &env,
+        &mut offering,
+        existing_subscription,
+        &mut new_subscription,
+    )?;
+    CLAIMED_TRIALS.save(deps.storage, trial_key, &env.block.time)?;
+
+    Ok(Response::new()
+        .add_messages(membership_msgs)
+        .add_attribute("action", "start_trial")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("creator", new_subscription.creator)
+        .add_attribute("nft_address", new_subscription.nft_address)
+        .add_attribute("token_id", token_id)
+        .add_attribute("end_time", new_subscription.end_time.to_string()))
+}
+
+/// Loads the subscriber's subscription to the NFT tier of `offering`, ensuring a new subscription
+/// can replace it
+fn load_replaceable_subscription(
+    storage: &dyn Storage,
+    env: &Env,
+    offering: &Offering,
+    subscriber: &str,
+) -> Result<Option<SubscriptionState>, ContractError> {
+    let existing_subscription = subscriptions().may_load(
+        storage,
+        (
+            offering.nft_address.clone(),
+            offering.token_id.clone(),
+            subscriber.to_string(),
+        ),
+    )?;
+    if let Some(existing_subscription) = &existing_subscription {
+        // A subscription to an earlier listing of this NFT can be replaced once it has ended
+        ensure!(
+            existing_subscription.offering_id != offering.offering_id
+                && !existing_subscription
+                    .status_at(env.block.time)
+                    .has_access(),
+            ContractError::CustomError {
+                msg: format!(
+                    "You already have a subscription to {} offering. Please renew (if inactive) or cancel it.",
+                    existing_subscription.nft_address
                 ),
-                payment_amount: open_subscription.payment_amount,
-                payment_pending: open_subscription.payment_amount - amount_sent, // Should Equal 0
-                payment_denom: open_subscription.payment_denom.clone(),
-                subscription_duration: open_subscription.subscription_duration,
-                is_active: true,
-            };
+            }
+        );
+    }
+    Ok(existing_subscription)
+}
 
-            subscriptions().save(deps.storage, user_key.clone(), &new_subscription)?;
+/// Builds an active subscription to `offering` with the offering's terms, running for
+/// `duration` seconds from now
+fn build_subscription(
+    storage: &mut dyn Storage,
+    env: &Env,
+    offering: &Offering,
+    subscriber: String,
+    duration: u64,
+) -> Result<SubscriptionState, ContractError> {
+    Ok(SubscriptionState {
+        subscription_id: get_and_increment_next_subscription_id(storage)?,
+        offering_id: offering.offering_id,
+        creator: offering.creator.clone(),
+        subscriber,
+        token_id: offering.token_id.clone(),
+        nft_address: offering.nft_address.clone(),
+        start_time: Expiration::AtTime(env.block.time),
+        end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
+        payment_amount: offering.payment_amount,
+        payment_pending: Uint128::zero(),
+        payment_asset: offering.payment_asset.clone(),
+        metadata: offering.metadata.clone(),
+        refund_policy: offering.refund_policy.clone(),
+        subscription_duration: offering.subscription_duration,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: offering.max_periods,
+        multi_period_discount: offering.multi_period_discount,
+        amount_paid: Uint128::zero(),
+        amount_credited: Uint128::zero(),
+        grace_period_seconds: offering.grace_period_seconds,
+        membership_token_id: None,
+    })
+}
 
-            Ok(Response::new()
-                .add_attribute("action", "subscribe")
-                .add_attribute("subscriber", subscriber)
-                .add_attribute("creator", new_subscription.creator)
-                .add_attribute("creator address", new_subscription.nft_address)
-                .add_attribute("start_time", new_subscription.start_time.to_string())
-                .add_attribute("end_time", new_subscription.end_time.to_string())
-                .add_attribute("is_active", new_subscription.is_active.to_string()))
+/// Saves `subscription` in place of `existing_subscription` and counts it for `offering`.
+/// Returns the messages moving the membership token to the new subscription.
+fn save_new_subscription(
+    storage: &mut dyn Storage,
+    env: &Env,
+    offering: &mut Offering,
+    existing_subscription: Option<SubscriptionState>,
+    subscription: &mut SubscriptionState,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut membership_msgs = match existing_subscription {
+        Some(mut existing_subscription) => {
+            burn_membership_token(storage, &mut existing_subscription)?
         }
-        Cw20HookMsg::Renew {
-            token_id,
-            nft_address,
-        } => {
-            let composite_key = (nft_address.clone(), subscriber.clone());
-            let mut subscription = subscriptions()
-                .may_load(deps.storage, composite_key.clone())?
+        None => vec![],
+    };
+    membership_msgs.extend(issue_membership_token(storage, env, subscription)?);
+
+    offering.subscription_count += 1;
+    offering.record_refundable_term(subscription);
+    offerings().save(
+        storage,
+        (offering.nft_address.clone(), offering.token_id.clone()),
+        offering,
+    )?;
+    subscriptions().save(
+        storage,
+        (
+            subscription.nft_address.clone(),
+            subscription.token_id.clone(),
+            subscription.subscriber.clone(),
+        ),
+        subscription,
+    )?;
+    Ok(membership_msgs)
+}
+
+fn execute_renew(
+    deps: DepsMut,
+    env: &Env,
+    payment: Payment,
+    token_id: String,
+    nft_address: String,
+) -> Result<Response, ContractError> {
+    let Payment {
+        subscriber,
+        asset: payment,
+        amount: amount_sent,
+        coupon,
+    } = payment;
+
+    let composite_key = (nft_address.clone(), token_id.clone(), subscriber.clone());
+    let mut subscription = subscriptions()
+        .may_load(deps.storage, composite_key.clone())?
+        .ok_or(ContractError::CustomError {
+            msg: format!(
+                "No subscription found for creator address {} and subscriber {}.",
+                nft_address, subscriber
+            ),
+        })?;
+
+    // Renewals are only accepted while the offering subscribed to is listed
+    ensure!(
+        is_offering_listed(deps.storage, &subscription)?,
+        ContractError::CustomError {
+            msg: "This subscription offering has been delisted.".to_string(),
+        }
+    );
+    let mut offering = load_offering(deps.storage, &nft_address, &token_id)?;
+
+    // Renewing a subscription without access starts a new term on the offering's current terms,
+    // while renewing one with access keeps the terms it was sold with
+    let status = subscription.status_at(env.block.time);
+    if !status.has_access() {
+        subscription.apply_offering_terms(&offering);
+    }
+
+    // Ensure the payment asset and amount match, discounted by the coupon if one is redeemed
+    ensure_accepted_payment(&subscription.payment_asset, &payment)?;
+    let coupon = coupon
+        .map(|code| {
+            load_redeemable_coupon(
+                deps.storage,
+                env,
+                subscription.offering_id,
+                &code,
+                Some(&subscriber),
+            )
+        })
+        .transpose()?;
+    let periods = periods_paid_for(
+        subscription.payment_amount,
+        subscription.max_periods,
+        subscription.multi_period_discount,
+        coupon.as_ref().map(|(_, coupon)| &coupon.discount),
+        amount_sent,
+    )?;
+    if let Some((code_hash, coupon)) = coupon {
+        redeem_coupon(
+            deps.storage,
+            subscription.offering_id,
+            code_hash,
+            coupon,
+            &subscriber,
+        )?;
+    }
+
+    let (creator_amount, rates_response) =
+        deduct_rates(deps.as_ref(), RENEW_ACTION, &payment, amount_sent)?;
+    let extension = subscription.subscription_duration.saturating_mul(periods);
+    match subscription.end_time {
+        // A free trial converts to a paid term starting when the trial ends
+        Expiration::AtTime(end_time) if status == SubscriptionStatus::Trialing => {
+            subscription.start_time = Expiration::AtTime(end_time);
+            subscription.end_time = Expiration::AtTime(end_time.plus_seconds(extension));
+            subscription.amount_paid = amount_sent;
+            subscription.amount_credited = creator_amount;
+        }
+        // Renewing early or within the grace period stacks the new periods onto the current
+        // term, so renewals while past due are backdated to the original end time
+        Expiration::AtTime(end_time) if status.has_access() => {
+            subscription.end_time = Expiration::AtTime(end_time.plus_seconds(extension));
+            subscription.amount_paid = subscription.amount_paid.checked_add(amount_sent)?;
+            subscription.amount_credited =
+                subscription.amount_credited.checked_add(creator_amount)?;
+        }
+        _ => {
+            subscription.start_time = Expiration::AtTime(env.block.time);
+            subscription.end_time = Expiration::AtTime(env.block.time.plus_seconds(extension));
+            subscription.amount_paid = amount_sent;
+            subscription.amount_credited = creator_amount;
+        }
+    }
+    subscription.status = status.transition(SubscriptionStatus::Active)?;
+    subscription.payment_pending = Uint128::zero();
+    let membership_msgs = issue_membership_token(deps.storage, env, &mut subscription)?;
+
+    // Save the updated subscription
+    subscriptions().save(deps.storage, composite_key, &subscription)?;
+    offering.record_refundable_term(&subscription);
+    offerings().save(
+        deps.storage,
+        (nft_address.clone(), token_id.clone()),
+        &offering,
+    )?;
+    let revenue_msgs = distribute_revenue(deps, &offering, &payment, creator_amount)?;
+
+    Ok(Response::new()
+        .add_messages(membership_msgs)
+        .add_submessages(rates_response.messages)
+        .add_submessages(revenue_msgs)
+        .add_events(rates_response.events)
+        .add_attribute("action", "renew_subscription")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("creator", subscription.creator)
+        .add_attribute("creator address", subscription.nft_address)
+        .add_attribute("token_id", token_id)
+        .add_attribute("new_start_time", subscription.start_time.to_string())
+        .add_attribute("new_end_time", subscription.end_time.to_string())
+        .add_attribute("is_active", subscription.status.has_access().to_string())
+        .add_attributes(rates_response.attributes))
+}
+
+/// Credits `amount` of a payment for `offering` to its revenue recipients by weight, or to its
+/// creator if it has none. Shares of recipients set to `forward` are sent right away. Rounding
+/// dust goes to the first recipient. An immediate payout forwards the whole amount instead.
+/// Returns the messages sending forwarded amounts.
+fn distribute_revenue(
+    deps: DepsMut,
+    offering: &Offering,
+    payment: &Asset,
+    amount: Uint128,
+) -> Result<Vec<SubMsg>, ContractError> {
+    if let Payout::Immediate { recipient } = &offering.payout {
+        if amount.is_zero() {
+            return Ok(vec![]);
+        }
+        return Ok(vec![recipient_msg(
+            deps.as_ref(),
+            recipient,
+            payment,
+            amount,
+        )?]);
+    }
+    if offering.revenue_split.is_empty() {
+        credit_balance(
+            deps.storage,
+            &CREATOR_REVENUE,
+            &offering.creator,
+            payment,
+            amount,
+        )?;
+        return Ok(vec![]);
+    }
+
+    let total_weight: u64 = offering
+        .revenue_split
+        .iter()
+        .map(|share| share.weight)
+        .sum();
+    let share_amounts: Vec<Uint128> = offering
+        .revenue_split
+        .iter()
+        .map(|share| amount.multiply_ratio(share.weight, total_weight))
+        .collect();
+    let dust = amount - share_amounts.iter().sum::<Uint128>();
+
+    let mut msgs = vec![];
+    for (i, (share, share_amount)) in offering.revenue_split.iter().zip(share_amounts).enumerate() {
+        let share_amount = if i == 0 {
+            share_amount + dust
+        } else {
+            share_amount
+        };
+        if share_amount.is_zero() {
+            continue;
+        }
+        if share.forward {
+            msgs.push(recipient_msg(
+                deps.as_ref(),
+                &share.recipient,
+                payment,
+                share_amount,
+            )?);
+        } else {
+            let recipient = share.recipient.address.get_raw_address(&deps.as_ref())?;
+            credit_balance(
+                deps.storage,
+                &CREATOR_REVENUE,
+                recipient.as_str(),
+                payment,
+                share_amount,
+            )?;
+        }
+    }
+    Ok(msgs)
+}
+
+/// Builds the message sending `amount` of `payment` to `recipient`, with its message if it has one
+fn recipient_msg(
+    deps: Deps,
+    recipient: &Recipient,
+    payment: &Asset,
+    amount: Uint128,
+) -> Result<SubMsg, ContractError> {
+    match payment {
+        Asset::NativeToken(denom) => {
+            recipient.generate_direct_msg(&deps, coins(amount.u128(), denom))
+        }
+        Asset::Cw20Token(contract_addr) => recipient.generate_msg_cw20(
+            &deps,
+            Cw20Coin {
+                address: contract_addr.clone(),
+                amount,
+            },
+        ),
+    }
+}
+
+/// Validates the revenue recipients of an offering
+fn validate_revenue_split(deps: Deps, revenue_split: &[RevenueShare]) -> Result<(), ContractError> {
+    ensure!(
+        revenue_split.len() <= MAX_REVENUE_RECIPIENTS,
+        ContractError::CustomError {
+            msg: format!(
+                "An offering can have at most {} revenue recipients.",
+                MAX_REVENUE_RECIPIENTS
+            ),
+        }
+    );
+    let mut total_weight: u64 = 0;
+    for share in revenue_split {
+        ensure!(
+            share.weight > 0 && share.weight <= MAX_REVENUE_WEIGHT,
+            ContractError::CustomError {
+                msg: format!(
+                    "Revenue split weights must be between 1 and {}.",
+                    MAX_REVENUE_WEIGHT
+                ),
+            }
+        );
+        total_weight =
+            total_weight
+                .checked_add(share.weight)
                 .ok_or(ContractError::CustomError {
-                    msg: format!(
-                        "No subscription found for creator address {} and subscriber {}.",
-                        nft_address, subscriber
-                    ),
+                    msg: "The total revenue split weight is too large.".to_string(),
                 })?;
+        ensure!(
+            share.forward || share.recipient.msg.is_none(),
+            ContractError::CustomError {
+                msg: "Only forwarded revenue shares can carry a message.".to_string(),
+            }
+        );
+        share.recipient.address.get_raw_address(&deps)?;
+    }
+    Ok(())
+}
+
+/// Validates the payout of an offering, which holds payments if it has a revenue split
+fn validate_payout(
+    deps: Deps,
+    payout: &Payout,
+    revenue_split: &[RevenueShare],
+) -> Result<(), ContractError> {
+    if let Payout::Immediate { recipient } = payout {
+        ensure!(
+            revenue_split.is_empty(),
+            ContractError::CustomError {
+                msg: "An immediate payout cannot be combined with a revenue split.".to_string(),
+            }
+        );
+        recipient.address.get_raw_address(&deps)?;
+    }
+    Ok(())
+}
+
+/// Validates the refund policy of an offering. Refunds are debited from the creator's revenue
+/// balance, so they cannot be offered when the revenue is split between other recipients or
+/// forwarded as it is paid.
+fn validate_refund_policy(
+    refund_policy: &RefundPolicy,
+    revenue_split: &[RevenueShare],
+    payout: &Payout,
+) -> Result<(), ContractError> {
+    ensure!(
+        matches!(payout, Payout::Custody) || *refund_policy == RefundPolicy::NoRefund,
+        ContractError::CustomError {
+            msg: "A refund policy cannot be combined with an immediate payout.".to_string(),
+        }
+    );
+    ensure!(
+        revenue_split.is_empty() || *refund_policy == RefundPolicy::NoRefund,
+        ContractError::CustomError {
+            msg: "A refund policy cannot be combined with a revenue split.".to_string(),
+        }
+    );
+    Ok(())
+}
+
+/// Deducts the rates configured for `action` from a payment of `amount`. Returns the amount left
+/// for the creator, with a response paying out the rates and reporting the fee.
+fn deduct_rates(
+    deps: Deps,
+    action: &str,
+    payment: &Asset,
+    amount: Uint128,
+) -> Result<(Uint128, Response), ContractError> {
+    let funds = match payment {
+        Asset::NativeToken(denom) => Funds::Native(Coin::new(amount.u128(), denom)),
+        Asset::Cw20Token(address) => Funds::Cw20(Cw20Coin {
+            address: address.clone(),
+            amount,
+        }),
+    };
+    let Some(rates) = ADOContract::default().query_deducted_funds(deps, action, funds)? else {
+        return Ok((amount, Response::new()));
+    };
+
+    let creator_amount = match rates.leftover_funds {
+        Funds::Native(coin) => coin.amount,
+        Funds::Cw20(coin) => coin.amount,
+    };
+    // Subscribers pay the exact price, so additive rates would be paid out of custody
+    ensure!(
+        rates.msgs.is_empty() || creator_amount < amount,
+        ContractError::CustomError {
+            msg: "Only deductive rates can be applied to subscription payments.".to_string(),
+        }
+    );
+
+    let fee = amount - creator_amount;
+    let mut response = Response::new()
+        .add_submessages(rates.msgs)
+        .add_events(rates.events);
+    if !fee.is_zero() {
+        response = response
+            .add_attribute("fee", fee)
+            .add_attribute("creator_amount", creator_amount);
+    }
+    Ok((creator_amount, response))
+}
+
+/// Amount of `token` this contract can currently pull from `owner` with `TransferFrom`
+fn query_pullable_amount(deps: Deps, env: &Env, token: &str, owner: &str) -> Uint128 {
+    let allowance: StdResult<AllowanceResponse> = deps.querier.query_wasm_smart(
+        token,
+        &Cw20QueryMsg::Allowance {
+            owner: owner.to_string(),
+            spender: env.contract.address.to_string(),
+        },
+    );
+    let balance: StdResult<BalanceResponse> = deps.querier.query_wasm_smart(
+        token,
+        &Cw20QueryMsg::Balance {
+            address: owner.to_string(),
+        },
+    );
+
+    match (allowance, balance) {
+        (Ok(allowance), Ok(balance)) if !allowance.expires.is_expired(&env.block) => {
+            allowance.allowance.min(balance.balance)
+        }
+        _ => Uint128::zero(),
+    }
+}
+
+/// Computes the refund owed for the current period of a subscription cancelled now. Refunds
+/// come out of what the creator was credited, so platform fees already paid out are not refunded.
+fn compute_refund(subscription: &SubscriptionState, env: &Env) -> Uint128 {
+    let (start_time, end_time) = match (subscription.start_time, subscription.end_time) {
+        (Expiration::AtTime(start_time), Expiration::AtTime(end_time)) => (start_time, end_time),
+        _ => return Uint128::zero(),
+    };
+    let now = env.block.time;
+
+    match subscription.refund_policy {
+        RefundPolicy::NoRefund => Uint128::zero(),
+        RefundPolicy::Prorated => {
+            if now >= end_time || end_time <= start_time {
+                return Uint128::zero();
+            }
+            let term = end_time.seconds() - start_time.seconds();
+            let remaining = (end_time.seconds() - now.seconds()).min(term);
+            subscription.amount_credited.multiply_ratio(remaining, term)
+        }
+        RefundPolicy::CoolingOff { window_seconds } => {
+            if now.seconds() <= start_time.seconds().saturating_add(window_seconds) {
+                subscription.amount_credited
+            } else {
+                Uint128::zero()
+            }
+        }
+    }
+}
+
+/// Price of paying for `periods` periods up front at `payment_amount` each, with the coupon
+/// discount taken off the total
+fn price_for_periods(
+    payment_amount: Uint128,
+    multi_period_discount: Option<Decimal>,
+    coupon: Option<&CouponDiscount>,
+    periods: u64,
+) -> Result<Uint128, ContractError> {
+    let price = payment_amount.checked_mul(Uint128::from(periods))?;
+    let price = match multi_period_discount {
+        Some(discount) if periods > 1 => price * (Decimal::one() - discount),
+        _ => price,
+    };
+    let Some(coupon) = coupon else {
+        return Ok(price);
+    };
+    // A fixed discount can reach the price if it was lowered after the coupon was created
+    let discounted_price = coupon.apply(price);
+    ensure!(
+        !discounted_price.is_zero(),
+        ContractError::CustomError {
+            msg: "This coupon's discount is not below the price.".to_string(),
+        }
+    );
+    Ok(discounted_price)
+}
+
+/// Resolves the number of periods `amount_sent` pays for, which must be the exact price of
+/// between 1 and `max_periods` periods. The multi-period discount only applies from 2 periods,
+/// above which the price grows with the number of periods, so the fewest periods priced at
+/// `amount_sent` or more are found by bisection in at most 64 steps.
+fn periods_paid_for(
+    payment_amount: Uint128,
+    max_periods: u64,
+    multi_period_discount: Option<Decimal>,
+    coupon: Option<&CouponDiscount>,
+    amount_sent: Uint128,
+) -> Result<u64, ContractError> {
+    if !payment_amount.is_zero() {
+        let price =
+            |periods| price_for_periods(payment_amount, multi_period_discount, coupon, periods);
+        if price(1)? == amount_sent {
+            return Ok(1);
+        }
+        let (mut low, mut high) = (2, max_periods);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if price(middle)? < amount_sent {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        if low <= max_periods && price(low)? == amount_sent {
+            return Ok(low);
+        }
+    }
+
+    let msg = if max_periods > 1 {
+        format!(
+            "Invalid payment amount. Expected the price of 1 to {} periods at {} each, received {}.",
+            max_periods, payment_amount, amount_sent
+        )
+    } else {
+        format!(
+            "Invalid payment amount. Expected {}, received {}.",
+            payment_amount, amount_sent
+        )
+    };
+    Err(ContractError::InvalidFunds { msg })
+}
+
+/// Validates the price and periods of an offering
+fn validate_offering_terms(offering: &Offering) -> Result<(), ContractError> {
+    ensure!(
+        !offering.payment_amount.is_zero(),
+        ContractError::CustomError {
+            msg: "Payment amount must be greater than 0.".to_string(),
+        }
+    );
+    ensure!(
+        offering.subscription_duration > 0,
+        ContractError::CustomError {
+            msg: "Subscription duration must be greater than 0.".to_string(),
+        }
+    );
+    validate_period_lengths(
+        offering.subscription_duration,
+        offering.grace_period_seconds,
+        offering.trial_duration,
+    )?;
+    validate_multi_period_terms(offering.max_periods, offering.multi_period_discount)?;
+    ensure!(
+        offering
+            .subscription_duration
+            .checked_mul(offering.max_periods)
+            .map_or(false, |prepaid| prepaid <= MAX_PERIOD_SECONDS),
+        ContractError::CustomError {
+            msg: format!(
+                "Max periods cannot pay for more than {} seconds up front.",
+                MAX_PERIOD_SECONDS
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Validates the lengths of an offering's period, grace period and free trial, which are bounded
+/// so that adding them to a block time cannot overflow
+fn validate_period_lengths(
+    duration: u64,
+    grace_period_seconds: u64,
+    trial_duration: u64,
+) -> Result<(), ContractError> {
+    ensure!(
+        duration <= MAX_PERIOD_SECONDS && trial_duration <= MAX_PERIOD_SECONDS,
+        ContractError::CustomError {
+            msg: format!(
+                "Subscription periods and free trials cannot be longer than {} seconds.",
+                MAX_PERIOD_SECONDS
+            ),
+        }
+    );
+    ensure!(
+        grace_period_seconds <= MAX_GRACE_PERIOD_SECONDS,
+        ContractError::CustomError {
+            msg: format!(
+                "Grace periods cannot be longer than {} seconds.",
+                MAX_GRACE_PERIOD_SECONDS
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Validates the multi-period terms of an offering
+fn validate_multi_period_terms(
+    max_periods: u64,
+    multi_period_discount: Option<Decimal>,
+) -> Result<(), ContractError> {
+    ensure!(
+        max_periods >= 1,
+        ContractError::CustomError {
+            msg: "Max periods must be at least 1.".to_string(),
+        }
+    );
+    ensure!(
+        multi_period_discount.map_or(true, |discount| discount < Decimal::one()),
+        ContractError::CustomError {
+            msg: "Multi-period discount must be less than 100%.".to_string(),
+        }
+    );
+    Ok(())
+}
+
+/// Loads the listed offering for the given NFT tier
+fn load_offering(
+    storage: &dyn Storage,
+    nft_address: &str,
+    token_id: &str,
+) -> Result<Offering, ContractError> {
+    offerings()
+        .may_load(storage, (nft_address.to_string(), token_id.to_string()))?
+        .ok_or(ContractError::CustomError {
+            msg: format!(
+                "No subscription offering found for creator address {} and token {}.",
+                nft_address, token_id
+            ),
+        })
+}
+
+/// Loads the coupon of an offering with `code`, ensuring it can be redeemed now, and by
+/// `subscriber` if given. Returns the coupon with the hash of its code.
+fn load_redeemable_coupon(
+    storage: &dyn Storage,
+    env: &Env,
+    offering_id: Uint128,
+    code: &str,
+    subscriber: Option<&str>,
+) -> Result<(String, Coupon), ContractError> {
+    let code_hash = coupon_code_hash(code);
+    let coupon = COUPONS
+        .may_load(storage, (offering_id.u128(), code_hash.clone()))?
+        .ok_or(ContractError::CustomError {
+            msg: "Invalid coupon code.".to_string(),
+        })?;
+
+    ensure!(
+        coupon.is_valid_at(env.block.time),
+        ContractError::CustomError {
+            msg: "This coupon is not valid at this time.".to_string(),
+        }
+    );
+    ensure!(
+        coupon
+            .max_redemptions
+            .map_or(true, |max| coupon.redemptions < max),
+        ContractError::CustomError {
+            msg: "This coupon has reached its maximum number of redemptions.".to_string(),
+        }
+    );
+    if let (Some(subscriber), Some(max)) = (subscriber, coupon.max_redemptions_per_address) {
+        let redemptions = COUPON_REDEMPTIONS
+            .may_load(
+                storage,
+                (
+                    offering_id.u128(),
+                    code_hash.clone(),
+                    subscriber.to_string(),
+                ),
+            )?
+            .unwrap_or_default();
+        ensure!(
+            redemptions < max,
+            ContractError::CustomError {
+                msg: "You have reached the maximum number of redemptions of this coupon."
+                    .to_string(),
+            }
+        );
+    }
+    Ok((code_hash, coupon))
+}
+
+/// Records a redemption of `coupon` by `subscriber`
+fn redeem_coupon(
+    storage: &mut dyn Storage,
+    offering_id: Uint128,
+    code_hash: String,
+    mut coupon: Coupon,
+    subscriber: &str,
+) -> Result<(), ContractError> {
+    coupon.redemptions += 1;
+    COUPONS.save(storage, (offering_id.u128(), code_hash.clone()), &coupon)?;
+    COUPON_REDEMPTIONS.update(
+        storage,
+        (offering_id.u128(), code_hash, subscriber.to_string()),
+        |redemptions| -> StdResult<_> { Ok(redemptions.unwrap_or_default() + 1) },
+    )?;
+    Ok(())
+}
+
+/// Resolves the key of `subscriber`'s subscription from either the NFT tier of its offering or
+/// its subscription ID
+fn subscription_key(
+    storage: &dyn Storage,
+    subscriber: &str,
+    nft_address: Option<String>,
+    token_id: Option<String>,
+    subscription_id: Option<Uint128>,
+) -> Result<(String, String, String), ContractError> {
+    match (nft_address, token_id, subscription_id) {
+        (Some(nft_address), Some(token_id), None) => {
+            Ok((nft_address, token_id, subscriber.to_string()))
+        }
+        (None, None, Some(subscription_id)) => {
+            let subscription = load_subscription_by_id(storage, subscription_id)?;
+            ensure!(
+                subscription.subscriber == subscriber,
+                ContractError::Unauthorized {}
+            );
+            Ok((
+                subscription.nft_address,
+                subscription.token_id,
+                subscription.subscriber,
+            ))
+        }
+        _ => Err(ContractError::CustomError {
+            msg: "Provide either a subscription ID or an NFT address and token ID.".to_string(),
+        }),
+    }
+}
+
+/// Loads a subscription by its subscription ID
+fn load_subscription_by_id(
+    storage: &dyn Storage,
+    subscription_id: Uint128,
+) -> Result<SubscriptionState, ContractError> {
+    subscriptions()
+        .idx
+        .subscription_id
+        .item(storage, subscription_id.u128())?
+        .map(|(_, subscription)| subscription)
+        .ok_or(ContractError::CustomError {
+            msg: format!("No subscription found with ID {}.", subscription_id),
+        })
+}
+
+/// Returns true if the offering the subscription was created from is still listed
+fn is_offering_listed(
+    storage: &dyn Storage,
+    subscription: &SubscriptionState,
+) -> Result<bool, ContractError> {
+    let offering = offerings().may_load(
+        storage,
+        (
+            subscription.nft_address.clone(),
+            subscription.token_id.clone(),
+        ),
+    )?;
+    Ok(offering.map_or(false, |offering| {
+        offering.offering_id == subscription.offering_id
+    }))
+}
+
+/// Messages minting the membership token of `subscription` to its subscriber with its current
+/// terms, burning the token issued before as CW721 token metadata cannot be updated. Empty unless
+/// membership tokens are enabled.
+fn issue_membership_token(
+    storage: &dyn Storage,
+    env: &Env,
+    subscription: &mut SubscriptionState,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let Some(membership_nft) = MEMBERSHIP_NFT.may_load(storage)? else {
+        return Ok(vec![]);
+    };
+    let mut msgs = burn_membership_token(storage, subscription)?;
+
+    let metadata = MembershipMetadata {
+        subscription_id: subscription.subscription_id,
+        subscriber: subscription.subscriber.clone(),
+        offering_id: subscription.offering_id,
+        nft_address: subscription.nft_address.clone(),
+        tier: subscription.token_id.clone(),
+        expires_at: subscription.end_time,
+    };
+    let token_id = subscription.subscription_id.to_string();
+    msgs.push(
+        WasmMsg::Execute {
+            contract_addr: membership_nft.into_string(),
+            msg: to_json_binary(&MembershipNftExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: subscription.subscriber.clone(),
+                token_uri: Some(format!(
+                    "data:application/json;base64,{}",
+                    to_json_binary(&metadata)?.to_base64()
+                )),
+                extension: MembershipTokenExtension {
+                    publisher: env.contract.address.to_string(),
+                },
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
+    subscription.membership_token_id = Some(token_id);
+    Ok(msgs)
+}
+
+/// Messages burning the membership token of `subscription`, if one was minted. The token is owned
+/// by the subscriber, so the membership CW721 must let its minter burn tokens.
+fn burn_membership_token(
+    storage: &dyn Storage,
+    subscription: &mut SubscriptionState,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let (Some(membership_nft), Some(token_id)) = (
+        MEMBERSHIP_NFT.may_load(storage)?,
+        subscription.membership_token_id.take(),
+    ) else {
+        return Ok(vec![]);
+    };
+    Ok(vec![WasmMsg::Execute {
+        contract_addr: membership_nft.into_string(),
+        msg: to_json_binary(&MembershipNftExecuteMsg::Burn { token_id })?,
+        funds: vec![],
+    }
+    .into()])
+}
+
+/// Ensures `payment` is the `expected` payment asset
+fn ensure_accepted_payment(expected: &Asset, payment: &Asset) -> Result<(), ContractError> {
+    ensure!(
+        expected == payment,
+        ContractError::InvalidFunds {
+            msg: format!(
+                "Invalid payment asset. Expected {}, received {}.",
+                expected, payment
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Returns the native denom of the accepted payment asset
+fn accepted_native_denom(payment_asset: &Asset) -> Result<String, ContractError> {
+    match payment_asset {
+        Asset::NativeToken(denom) => Ok(denom.clone()),
+        Asset::Cw20Token(_) => Err(ContractError::InvalidFunds {
+            msg: "This subscription does not accept native payments.".to_string(),
+        }),
+    }
+}
+
+pub fn handle_receive_cw721(
+    mut ctx: ExecuteContext,
+    receive_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // Validate that the NFT contract is authorized
+    ADOContract::default().is_permissioned(
+        ctx.deps.branch(),
+        ctx.env.clone(),
+        SEND_NFT_ACTION,
+        ctx.info.sender.clone(),
+    )?;
+
+    let Cw721ReceiveMsg {
+        sender,
+        token_id,
+        msg,
+    } = receive_msg;
+    let hook_msg: Cw721HookMsg = from_json(&msg)?;
+
+    match hook_msg {
+        Cw721HookMsg::RegisterSubscription {
+            duration,
+            payment_amount,
+            payment_asset,
+            metadata,
+            refund_policy,
+            max_periods,
+            multi_period_discount,
+            grace_period_seconds,
+            trial_duration,
+            transferable,
+            revenue_split,
+            payout,
+        } => {
+            let revenue_split = revenue_split.unwrap_or_default();
+            validate_revenue_split(ctx.deps.as_ref(), &revenue_split)?;
+            let payout = payout.unwrap_or(Payout::Custody);
+            validate_payout(ctx.deps.as_ref(), &payout, &revenue_split)?;
+            let refund_policy = refund_policy.unwrap_or(RefundPolicy::NoRefund);
+            validate_refund_policy(&refund_policy, &revenue_split, &payout)?;
+
+            // Validate the payment asset the offering is bound to
+            match &payment_asset {
+                Asset::NativeToken(denom) => {
+                    ensure!(
+                        !denom.is_empty(),
+                        ContractError::CustomError {
+                            msg: "Payment denom cannot be empty.".to_string(),
+                        }
+                    );
+                }
+                Asset::Cw20Token(contract_addr) => {
+                    ctx.deps.api.addr_validate(contract_addr)?;
+                }
+            }
+
+            // Composite key: (nft_address, tier token)
+            let offering_key = (ctx.info.sender.to_string(), token_id.clone());
+
+            // Check if the offering already exists
+            if offerings()
+                .may_load(ctx.deps.storage, offering_key.clone())?
+                .is_some()
+            {
+                return Err(ContractError::CustomError {
+                    msg: "Subscription offering already exists for this NFT.".to_string(),
+                });
+            }
+            let offering_id = get_and_increment_next_subscription_id(ctx.deps.storage)?;
+
+            let offering = Offering {
+                offering_id,
+                creator: sender.clone(), // The creator is the sender of the NFT
+                nft_address: ctx.info.sender.to_string(), // Address of the CW721 contract
+                token_id,
+                payment_amount,
+                payment_asset,
+                subscription_duration: duration,
+                metadata,
+                refund_policy,
+                max_periods: max_periods.unwrap_or(1),
+                multi_period_discount,
+                grace_period_seconds: grace_period_seconds.unwrap_or_default(),
+                trial_duration: trial_duration.unwrap_or_default(),
+                transferable: transferable.unwrap_or_default(),
+                revenue_split,
+                payout,
+                subscription_count: 0,
+                refundable_until: Timestamp::from_nanos(0),
+            };
+            validate_offering_terms(&offering)?;
+
+            offerings().save(ctx.deps.storage, offering_key, &offering)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "register_subscription")
+                .add_attribute("creator", sender)
+                .add_attribute("offering_id", offering_id.to_string())
+                .add_attribute("token_id", offering.token_id)
+                .add_attribute("nft_address", offering.nft_address)
+                .add_attribute("duration", duration.to_string()))
+        }
+    }
+}
+
+pub fn execute_update_offering(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+    update: OfferingUpdate,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, env, info, ..
+    } = ctx;
+    let OfferingUpdate {
+        payment_amount,
+        duration,
+        metadata,
+        refund_policy,
+        max_periods,
+        multi_period_discount,
+        grace_period_seconds,
+        trial_duration,
+        transferable,
+        revenue_split,
+        payout,
+    } = update;
+
+    nonpayable(&info)?;
+
+    let mut offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    ensure!(
+        info.sender == offering.creator,
+        ContractError::Unauthorized {}
+    );
+
+    // Subscriptions keep the terms they were sold with until they are renewed after losing access
+    if let Some(payment_amount) = payment_amount {
+        offering.payment_amount = payment_amount;
+    }
+    if let Some(duration) = duration {
+        offering.subscription_duration = duration;
+    }
+    if metadata.is_some() {
+        offering.metadata = metadata;
+    }
+    if let Some(refund_policy) = refund_policy {
+        offering.refund_policy = refund_policy;
+    }
+    if let Some(max_periods) = max_periods {
+        offering.max_periods = max_periods;
+    }
+    if multi_period_discount.is_some() {
+        offering.multi_period_discount = multi_period_discount;
+    }
+    if let Some(grace_period_seconds) = grace_period_seconds {
+        offering.grace_period_seconds = grace_period_seconds;
+    }
+    if let Some(trial_duration) = trial_duration {
+        offering.trial_duration = trial_duration;
+    }
+    if let Some(transferable) = transferable {
+        offering.transferable = transferable;
+    }
+    if let Some(revenue_split) = revenue_split {
+        validate_revenue_split(deps.as_ref(), &revenue_split)?;
+        offering.revenue_split = revenue_split;
+    }
+    if let Some(payout) = payout {
+        offering.payout = payout;
+    }
+    // Refunds of terms sold while revenue was held are debited from the creator's revenue, which
+    // no longer receives the payments once they are forwarded
+    ensure!(
+        !offering.forwards_revenue() || env.block.time >= offering.refundable_until,
+        ContractError::CustomError {
+            msg: format!(
+                "Revenue cannot be forwarded until refundable subscriptions end at {}.",
+                offering.refundable_until
+            ),
+        }
+    );
+    validate_payout(deps.as_ref(), &offering.payout, &offering.revenue_split)?;
+    validate_refund_policy(
+        &offering.refund_policy,
+        &offering.revenue_split,
+        &offering.payout,
+    )?;
+    validate_offering_terms(&offering)?;
+
+    offerings().save(
+        deps.storage,
+        (nft_address.clone(), token_id.clone()),
+        &offering,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_offering")
+        .add_attribute("nft_address", nft_address)
+        .add_attribute("token_id", token_id)
+        .add_attribute("payment_amount", offering.payment_amount)
+        .add_attribute("duration", offering.subscription_duration.to_string()))
+}
+
+pub fn execute_create_coupon(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+    code: String,
+    coupon: Coupon,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
+
+    nonpayable(&info)?;
+
+    let offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    ensure!(
+        info.sender == offering.creator,
+        ContractError::Unauthorized {}
+    );
+
+    ensure!(
+        !code.is_empty(),
+        ContractError::CustomError {
+            msg: "Coupon code cannot be empty.".to_string(),
+        }
+    );
+    let valid_discount = match coupon.discount {
+        CouponDiscount::Percent(percent) => !percent.is_zero() && percent < Decimal::one(),
+        CouponDiscount::Fixed(amount) => !amount.is_zero() && amount < offering.payment_amount,
+    };
+    ensure!(
+        valid_discount,
+        ContractError::CustomError {
+            msg: "Coupon discount must be a nonzero amount below the price or a percentage below 100%."
+                .to_string(),
+        }
+    );
+    if let (Some(valid_from), Some(valid_until)) = (coupon.valid_from, coupon.valid_until) {
+        ensure!(
+            valid_from < valid_until,
+            ContractError::CustomError {
+                msg: "Coupon validity must start before it ends.".to_string(),
+            }
+        );
+    }
+
+    let key = (offering.offering_id.u128(), coupon_code_hash(&code));
+    ensure!(
+        !COUPONS.has(deps.storage, key.clone()),
+        ContractError::CustomError {
+            msg: "A coupon with this code already exists for this offering.".to_string(),
+        }
+    );
+    COUPONS.save(deps.storage, key, &coupon)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_coupon")
+        .add_attribute("offering_id", offering.offering_id)
+        .add_attribute("nft_address", nft_address)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_delete_coupon(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+    code: String,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
+
+    nonpayable(&info)?;
+
+    let offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    ensure!(
+        info.sender == offering.creator,
+        ContractError::Unauthorized {}
+    );
+
+    let key = (offering.offering_id.u128(), coupon_code_hash(&code));
+    ensure!(
+        COUPONS.has(deps.storage, key.clone()),
+        ContractError::CustomError {
+            msg: "Invalid coupon code.".to_string(),
+        }
+    );
+    COUPONS.remove(deps.storage, key);
+
+    Ok(Response::new()
+        .add_attribute("action", "delete_coupon")
+        .add_attribute("offering_id", offering.offering_id)
+        .add_attribute("nft_address", nft_address)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_delist_offering(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
+
+    nonpayable(&info)?;
+
+    let offering = load_offering(deps.storage, &nft_address, &token_id)?;
+    ensure!(
+        info.sender == offering.creator,
+        ContractError::Unauthorized {}
+    );
+
+    // Removing the offering blocks new subscriptions and renewals, active subscribers keep
+    // access until the end of their paid period
+    offerings().remove(deps.storage, (nft_address.clone(), token_id.clone()))?;
+
+    // Return the deposited NFT to the creator
+    let return_nft_msg = WasmMsg::Execute {
+        contract_addr: nft_address.clone(),
+        msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: offering.creator.clone(),
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(return_nft_msg)
+        .add_attribute("action", "delist_offering")
+        .add_attribute("creator", offering.creator)
+        .add_attribute("nft_address", nft_address)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_cancel(
+    ctx: ExecuteContext,
+    nft_address: Option<String>,
+    token_id: Option<String>,
+    subscription_id: Option<Uint128>,
+    immediate: bool,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, env, info, ..
+    } = ctx;
+
+    let composite_key = subscription_key(
+        deps.storage,
+        info.sender.as_str(),
+        nft_address,
+        token_id,
+        subscription_id,
+    )?;
+    let nft_address = composite_key.0.clone();
+
+    // Fetch the subscription
+    let mut subscription = subscriptions()
+        .may_load(deps.storage, composite_key.clone())?
+        .ok_or(ContractError::CustomError {
+            msg: format!(
+                "No subscription found for address {} and subscriber {}.",
+                nft_address, info.sender
+            ),
+        })?;
+
+    let status = subscription.status_at(env.block.time);
+
+    // Ensure the subscription is active
+    if !status.has_access() {
+        return Err(ContractError::CustomError {
+            msg: "Subscription is already inactive.".to_string(),
+        });
+    }
+
+    // Keep access until the end of the paid period and stop renewing afterwards
+    if !immediate {
+        ensure!(
+            status != SubscriptionStatus::CancelScheduled,
+            ContractError::CustomError {
+                msg: "Subscription is already scheduled to cancel at the end of the period."
+                    .to_string(),
+            }
+        );
+        subscription.status = status.transition(SubscriptionStatus::CancelScheduled)?;
+        subscriptions().save(deps.storage, composite_key, &subscription)?;
+
+        return Ok(Response::new()
+            .add_attribute("action", "cancel_subscription")
+            .add_attribute("creator", subscription.creator)
+            .add_attribute("subscriber", info.sender.to_string())
+            .add_attribute("is_active", subscription.status.has_access().to_string())
+            .add_attribute("status", subscription.status.to_string())
+            .add_attribute("end_time", subscription.end_time.to_string()));
+    }
+
+    // Refunds are paid from the creator's unclaimed revenue and are never cut short
+    let available_revenue = CREATOR_REVENUE
+        .may_load(
+            deps.storage,
+            (
+                subscription.creator.clone(),
+                subscription.payment_asset.to_string(),
+            ),
+        )?
+        .unwrap_or_default();
+    let refund = compute_refund(&subscription, &env);
+    ensure!(
+        refund <= available_revenue,
+        ContractError::CustomError {
+            msg: format!(
+                "The creator's unclaimed revenue of {} cannot cover the refund of {}.",
+                available_revenue, refund
+            ),
+        }
+    );
+
+    // The period is ended now so its times record when access stopped
+    subscription.status = status.transition(SubscriptionStatus::Cancelled)?;
+    subscription.payment_pending = subscription.payment_amount;
+    subscription.end_time = Expiration::AtTime(env.block.time);
+    let membership_msgs = burn_membership_token(deps.storage, &mut subscription)?;
+    subscriptions().save(deps.storage, composite_key, &subscription)?;
+
+    let mut response = Response::new()
+        .add_messages(membership_msgs)
+        .add_attribute("action", "cancel_subscription")
+        .add_attribute("creator", subscription.creator.clone())
+        .add_attribute("subscriber", info.sender.to_string())
+        .add_attribute("is_active", subscription.status.has_access().to_string())
+        .add_attribute("status", subscription.status.to_string());
+
+    if !refund.is_zero() {
+        debit_balance(
+            deps.storage,
+            &CREATOR_REVENUE,
+            &subscription.creator,
+            &subscription.payment_asset,
+            refund,
+        )?;
+        response = response
+            .add_message(
+                subscription
+                    .payment_asset
+                    .transfer_msg(&info.sender, refund)?,
+            )
+            .add_attribute("refund", refund);
+    }
+
+    Ok(response)
+}
+
+pub fn execute_transfer_subscription(
+    ctx: ExecuteContext,
+    offering_id: Uint128,
+    recipient: AndrAddr,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        deps, env, info, ..
+    } = ctx;
+
+    nonpayable(&info)?;
+
+    let recipient = recipient.get_raw_address(&deps.as_ref())?.into_string();
+    ensure!(
+        recipient != info.sender,
+        ContractError::CustomError {
+            msg: "Cannot transfer a subscription to yourself.".to_string(),
+        }
+    );
+
+    let ((nft_address, token_id, _), mut subscription) =
+        find_subscriber_subscription(deps.storage, info.sender.to_string(), offering_id)?.ok_or(
+            ContractError::CustomError {
+                msg: format!(
+                    "No subscription found for offering {} and subscriber {}.",
+                    offering_id, info.sender
+                ),
+            },
+        )?;
+
+    // The creator decides whether subscriptions to the offering can change hands
+    let offering = offerings()
+        .may_load(deps.storage, (nft_address.clone(), token_id.clone()))?
+        .filter(|offering| offering.offering_id == offering_id)
+        .ok_or(ContractError::CustomError {
+            msg: "This subscription offering has been delisted.".to_string(),
+        })?;
+    ensure!(
+        offering.transferable,
+        ContractError::CustomError {
+            msg: "Subscriptions to this offering are not transferable.".to_string(),
+        }
+    );
+
+    let recipient_key = (nft_address.clone(), token_id.clone(), recipient.clone());
+    ensure!(
+        !subscriptions().has(deps.storage, recipient_key.clone()),
+        ContractError::CustomError {
+            msg: format!("{} already has a subscription to this offering.", recipient),
+        }
+    );
+
+    // Re-key the subscription under the recipient. Auto-renewal pulls from the previous
+    // subscriber's allowance, so it is turned off.
+    subscriptions().remove(
+        deps.storage,
+        (nft_address, token_id, info.sender.to_string()),
+    )?;
+    subscription.subscriber = recipient.clone();
+    subscription.auto_renew = false;
+    let membership_msgs = if subscription.membership_token_id.is_some() {
+        issue_membership_token(deps.storage, &env, &mut subscription)?
+    } else {
+        vec![]
+    };
+    subscriptions().save(deps.storage, recipient_key, &subscription)?;
+
+    Ok(Response::new()
+        .add_messages(membership_msgs)
+        .add_attribute("action", "transfer_subscription")
+        .add_attribute("subscription_id", subscription.subscription_id)
+        .add_event(
+            Event::new("subscription_transferred")
+                .add_attribute("subscription_id", subscription.subscription_id)
+                .add_attribute("offering_id", offering_id)
+                .add_attribute("from", info.sender.to_string())
+                .add_attribute("to", recipient),
+        ))
+}
+
+pub fn execute_set_auto_renew(
+    ctx: ExecuteContext,
+    nft_address: String,
+    token_id: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
+
+    nonpayable(&info)?;
+
+    let composite_key = (nft_address.clone(), token_id, info.sender.to_string());
+    let mut subscription = subscriptions()
+        .may_load(deps.storage, composite_key.clone())?
+        .ok_or(ContractError::CustomError {
+            msg: format!(
+                "No subscription found for address {} and subscriber {}.",
+                nft_address, info.sender
+            ),
+        })?;
+
+    // Renewals are pulled from a CW20 allowance granted to this contract
+    ensure!(
+        !enabled || matches!(subscription.payment_asset, Asset::Cw20Token(_)),
+        ContractError::CustomError {
+            msg: "Auto-renewal is only available for subscriptions paid in CW20 tokens."
+                .to_string(),
+        }
+    );
+
+    subscription.auto_renew = enabled;
+    subscriptions().save(deps.storage, composite_key, &subscription)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_auto_renew")
+        .add_attribute("subscriber", info.sender.to_string())
+        .add_attribute("nft_address", nft_address)
+        .add_attribute("auto_renew", enabled.to_string()))
+}
+
+pub fn execute_process_renewals(
+    ctx: ExecuteContext,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let ExecuteContext {
+        mut deps,
+        env,
+        info,
+        ..
+    } = ctx;
+
+    nonpayable(&info)?;
+
+    let limit = limit.map(u64::from).unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // The renewal index only orders subscriptions the crank acts on, by when they are due, so
+    // every entry before the block time is due
+    let due: Vec<((String, String, String), SubscriptionState)> = subscriptions()
+        .idx
+        .renewal
+        .prefix_range(
+            deps.storage,
+            None,
+            Some(PrefixBound::exclusive(env.block.time.nanos())),
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    // Amount still pullable per (token, subscriber) within this batch
+    let mut pullable: BTreeMap<(String, String), Uint128> = BTreeMap::new();
+    let mut response = Response::new();
+    let (mut renewed, mut past_due, mut lapsed) = (0u32, 0u32, 0u32);
+
+    for ((nft_address, token_id, subscriber), mut subscription) in due {
+        let in_grace_period = subscription.status_at(env.block.time) == SubscriptionStatus::PastDue;
+        let listed = is_offering_listed(deps.storage, &subscription)?;
+
+        let prepaid = SUBSCRIBER_BALANCES
+            .may_load(
+                deps.storage,
+                (subscriber.clone(), subscription.payment_asset.to_string()),
+            )?
+            .unwrap_or_default();
+
+        let paid = if !listed {
+            false
+        } else if prepaid >= subscription.payment_amount {
+            debit_balance(
+                deps.storage,
+                &SUBSCRIBER_BALANCES,
+                &subscriber,
+                &subscription.payment_asset,
+                subscription.payment_amount,
+            )?;
+            true
+        } else if let (true, Asset::Cw20Token(token)) =
+            (subscription.auto_renew, &subscription.payment_asset)
+        {
+            let available = *pullable
+                .entry((token.clone(), subscriber.clone()))
+                .or_insert_with(|| query_pullable_amount(deps.as_ref(), &env, token, &subscriber));
+            if available >= subscription.payment_amount {
+                pullable.insert(
+                    (token.clone(), subscriber.clone()),
+                    available - subscription.payment_amount,
+                );
+                response = response.add_message(WasmMsg::Execute {
+                    contract_addr: token.clone(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: subscriber.clone(),
+                        recipient: env.contract.address.to_string(),
+                        amount: subscription.payment_amount,
+                    })?,
+                    funds: vec![],
+                });
+            }
+            available >= subscription.payment_amount
+        } else {
+            false
+        };
+
+        if paid {
+            let (creator_amount, rates_response) = deduct_rates(
+                deps.as_ref(),
+                RENEW_ACTION,
+                &subscription.payment_asset,
+                subscription.payment_amount,
+            )?;
+            let mut offering = load_offering(deps.storage, &nft_address, &token_id)?;
+            let revenue_msgs = distribute_revenue(
+                deps.branch(),
+                &offering,
+                &subscription.payment_asset,
+                creator_amount,
+            )?;
+            response = response
+                .add_submessages(rates_response.messages)
+                .add_events(rates_response.events)
+                .add_submessages(revenue_msgs);
+
+            // Within the grace period the new period starts at the original end time
+            let period_start = match subscription.end_time {
+                Expiration::AtTime(end_time) if in_grace_period => end_time,
+                _ => env.block.time,
+            };
+            subscription.start_time = Expiration::AtTime(period_start);
+            subscription.end_time =
+                Expiration::AtTime(period_start.plus_seconds(subscription.subscription_duration));
+            subscription.payment_pending = Uint128::zero();
+            subscription.amount_paid = subscription.payment_amount;
+            subscription.amount_credited = creator_amount;
+            subscription.status = subscription.status.transition(SubscriptionStatus::Active)?;
+            offering.record_refundable_term(&subscription);
+            offerings().save(
+                deps.storage,
+                (nft_address.clone(), token_id.clone()),
+                &offering,
+            )?;
+            response = response.add_messages(issue_membership_token(
+                deps.storage,
+                &env,
+                &mut subscription,
+            )?);
+            renewed += 1;
+        } else if in_grace_period {
+            // Keep access until the grace period ends, recording what is owed
+            subscription.status = subscription
+                .status
+                .transition(SubscriptionStatus::PastDue)?;
+            subscription.payment_pending = subscription.payment_amount;
+            past_due += 1;
+        } else {
+            // Lapse the subscription rather than failing the whole batch, recording what is owed
+            subscription.status = subscription
+                .status
+                .transition(SubscriptionStatus::Expired)?;
+            subscription.auto_renew = false;
+            subscription.payment_pending = subscription.payment_amount;
+            response =
+                response.add_messages(burn_membership_token(deps.storage, &mut subscription)?);
+            lapsed += 1;
+        }
+
+        subscriptions().save(
+            deps.storage,
+            (nft_address, token_id, subscriber),
+            &subscription,
+        )?;
+    }
+
+    Ok(response
+        .add_attribute("action", "process_renewals")
+        .add_attribute("renewed", renewed.to_string())
+        .add_attribute("lapsed", lapsed.to_string())
+        .add_attribute("past_due", past_due.to_string()))
+}
+
+pub fn execute_withdraw_revenue(
+    ctx: ExecuteContext,
+    asset: Asset,
+    amount: Option<Uint128>,
+    recipient: Option<AndrAddr>,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
+
+    nonpayable(&info)?;
+
+    let creator = info.sender.to_string();
+
+    let balance = CREATOR_REVENUE
+        .may_load(deps.storage, (creator.clone(), asset.to_string()))?
+        .unwrap_or_default();
+    let amount = amount.unwrap_or(balance);
+
+    ensure!(
+        !amount.is_zero(),
+        ContractError::InvalidFunds {
+            msg: "Cannot withdraw a 0 amount.".to_string(),
+        }
+    );
+    ensure!(
+        amount <= balance,
+        ContractError::InvalidFunds {
+            msg: format!(
+                "Insufficient revenue. Available {}, requested {}.",
+                balance, amount
+            ),
+        }
+    );
 
-            // Ensure the payment amount matches
-            ensure!(
-                amount_sent == subscription.payment_amount,
-                ContractError::InvalidFunds {
-                    msg: format!(
-                        "Invalid payment amount. Expected {}, received {}.",
-                        subscription.payment_amount, amount_sent
-                    ),
-                }
-            );
+    debit_balance(deps.storage, &CREATOR_REVENUE, &creator, &asset, amount)?;
 
-            if subscription.is_active {
-                if let Expiration::AtTime(end_time) = subscription.end_time {
-                    if env.block.time > end_time {
-                        subscription.is_active = false; // Mark as inactive if expired
-                        subscription.payment_pending = subscription.payment_amount;
-                    } else {
-                        return Err(ContractError::CustomError {
-                            msg: "Subscription is already active.".to_string(),
-                        });
-                    }
-                }
-            }
-            subscription.start_time = Expiration::AtTime(ctx.env.block.time);
-            subscription.end_time = Expiration::AtTime(
-                ctx.env
-                    .block
-                    .time
-                    .plus_seconds(subscription.subscription_duration),
-            );
-            subscription.is_active = true;
-            subscription.payment_pending = subscription.payment_amount - amount_sent; // Should equal 0
+    let recipient = match recipient {
+        Some(recipient) => recipient.get_raw_address(&deps.as_ref())?,
+        None => info.sender,
+    };
 
-            // Save the updated subscription
-            subscriptions().save(deps.storage, composite_key, &subscription)?;
+    let transfer_msg = asset.transfer_msg(&recipient, amount)?;
 
-            Ok(Response::new()
-                .add_attribute("action", "renew_subscription")
-                .add_attribute("subscriber", subscriber)
-                .add_attribute("creator", subscription.creator)
-                .add_attribute("creator address", subscription.nft_address)
-                .add_attribute("token_id", token_id)
-                .add_attribute("new_start_time", subscription.start_time.to_string())
-                .add_attribute("new_end_time", subscription.end_time.to_string())
-                .add_attribute("is_active", subscription.is_active.to_string()))
-        }
-    }
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attribute("action", "withdraw_revenue")
+        .add_attribute("creator", creator)
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
 }
 
-pub fn handle_receive_cw721(
-    mut ctx: ExecuteContext,
-    receive_msg: Cw721ReceiveMsg,
-) -> Result<Response, ContractError> {
-    // Validate that the NFT contract is authorized
-    ADOContract::default().is_permissioned(
-        ctx.deps.branch(),
-        ctx.env.clone(),
-        SEND_NFT_ACTION,
-        ctx.info.sender.clone(),
-    )?;
+pub fn handle_native_deposit(ctx: ExecuteContext) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
 
-    let Cw721ReceiveMsg {
-        sender,
-        token_id,
-        msg,
-    } = receive_msg;
-    let hook_msg: Cw721HookMsg = from_json(&msg)?;
+    let coin = one_coin(&info)?;
 
-    match hook_msg {
-        Cw721HookMsg::RegisterSubscription {
-            duration,
-            payment_amount,
-        } => {
-            // Composite key: (nft_address, empty subscriber)
-            let composite_key = (ctx.info.sender.to_string(), String::new());
+    execute_deposit(
+        deps,
+        info.sender.to_string(),
+        Asset::NativeToken(coin.denom),
+        coin.amount,
+    )
+}
 
-            // Check if the subscription already exists
-            if subscriptions()
-                .may_load(ctx.deps.storage, composite_key.clone())?
-                .is_some()
-            {
-                return Err(ContractError::CustomError {
-                    msg: "Subscription offering already exists for this NFT.".to_string(),
-                });
-            }
-            let subscription_id = get_and_increment_next_subscription_id(ctx.deps.storage)?;
+/// Credits a deposit to the subscriber's prepaid balance
+pub fn execute_deposit(
+    deps: DepsMut,
+    subscriber: String,
+    asset: Asset,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let balance = credit_balance(
+        deps.storage,
+        &SUBSCRIBER_BALANCES,
+        &subscriber,
+        &asset,
+        amount,
+    )?;
 
-            let subscription = SubscriptionState {
-                subscription_id,
-                creator: sender.clone(), // The creator is the sender of the NFT
-                subscriber: String::new(), // No subscriber yet; empty string or None
-                token_id,
-                nft_address: ctx.info.sender.to_string(), // Address of the CW721 contract
-                start_time: Expiration::Never {},         // Start time is not applicable yet
-                end_time: Expiration::Never {},           // No subscription period yet
-                payment_amount,
-                payment_pending: payment_amount, // Full amount pending
-                payment_denom: "CW20".to_string(), // Default
-                subscription_duration: duration,
-                is_active: false,
-            };
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("balance", balance))
+}
 
-            subscriptions().save(
-                ctx.deps.storage,
-                (
-                    subscription.nft_address.clone(),
-                    subscription.subscriber.clone(),
-                ),
-                &subscription,
-            )?;
+pub fn execute_withdraw_balance(
+    ctx: ExecuteContext,
+    asset: Asset,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let ExecuteContext { deps, info, .. } = ctx;
 
-            Ok(Response::new()
-                .add_attribute("action", "register_subscription")
-                .add_attribute("creator", sender)
-                .add_attribute("subscription_id", subscription_id.to_string())
-                .add_attribute("token_id", subscription.token_id)
-                .add_attribute("nft_address", subscription.nft_address)
-                .add_attribute("duration", duration.to_string()))
-        }
-    }
-}
+    nonpayable(&info)?;
 
-pub fn execute_cancel(ctx: ExecuteContext, nft_address: String) -> Result<Response, ContractError> {
-    let ExecuteContext {
-        deps, env, info, ..
-    } = ctx;
+    let subscriber = info.sender.to_string();
 
-    let composite_key = (nft_address.clone(), info.sender.to_string());
+    let balance = SUBSCRIBER_BALANCES
+        .may_load(deps.storage, (subscriber.clone(), asset.to_string()))?
+        .unwrap_or_default();
+    let amount = amount.unwrap_or(balance);
 
-    // Fetch the subscription
-    let mut subscription = subscriptions()
-        .may_load(deps.storage, composite_key.clone())?
-        .ok_or(ContractError::CustomError {
+    ensure!(
+        !amount.is_zero(),
+        ContractError::InvalidFunds {
+            msg: "Cannot withdraw a 0 amount.".to_string(),
+        }
+    );
+    ensure!(
+        amount <= balance,
+        ContractError::InvalidFunds {
             msg: format!(
-                "No subscription found for address {} and subscriber {}.",
-                nft_address, info.sender
+                "Insufficient balance. Available {}, requested {}.",
+                balance, amount
             ),
-        })?;
-
-    if subscription.is_active {
-        if let Expiration::AtTime(end_time) = subscription.end_time {
-            if env.block.time > end_time {
-                subscription.is_active = false; // Mark as inactive if expired
-                subscription.payment_pending = subscription.payment_amount;
-            }
         }
-    }
+    );
 
-    // Ensure the subscription is active
-    if !subscription.is_active {
-        return Err(ContractError::CustomError {
-            msg: "Subscription is already inactive.".to_string(),
-        });
-    }
-    subscription.is_active = false;
-    subscription.payment_pending = subscription.payment_amount;
-    subscription.start_time = Expiration::Never {};
-    subscription.end_time = Expiration::Never {};
-    subscriptions().save(deps.storage, composite_key, &subscription)?;
+    debit_balance(
+        deps.storage,
+        &SUBSCRIBER_BALANCES,
+        &subscriber,
+        &asset,
+        amount,
+    )?;
+
+    let transfer_msg = asset.transfer_msg(&info.sender, amount)?;
 
     Ok(Response::new()
-        .add_attribute("action", "cancel_subscription")
-        .add_attribute("creator", subscription.creator)
-        .add_attribute("subscriber", info.sender.to_string())
-        .add_attribute("is_active", subscription.is_active.to_string())
-        .add_attribute("status", "cancelled"))
+        .add_message(transfer_msg)
+        .add_attribute("action", "withdraw_balance")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("amount", amount))
 }
 
 #[entry_point]
@@ -418,8 +2348,19 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
     match msg {
         QueryMsg::Subscription {
             creator,
+            token_id,
+            subscriber,
+        } => encode_binary(&query_subscription(
+            deps, creator, token_id, env, subscriber,
+        )?),
+        QueryMsg::SubscriptionById { id } => {
+            encode_binary(&query_subscription_by_id(deps, env, id)?)
+        }
+        QueryMsg::HasAccess {
             subscriber,
-        } => encode_binary(&query_subscription(deps, creator, env, subscriber)?),
+            offering,
+            at_time,
+        } => encode_binary(&query_has_access(deps, env, subscriber, offering, at_time)?),
         QueryMsg::SubscriptionsForCreator {
             creator,
             start_after,
@@ -449,7 +2390,6 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
         } => encode_binary(&query_subscription_ids_for_creator(
             deps,
             creator,
-            env,
             start_after,
             limit,
         )?),
@@ -460,13 +2400,49 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
         } => encode_binary(&query_subscription_ids_for_subscriber(
             deps,
             subscriber,
-            env,
             start_after,
             limit,
         )?),
         QueryMsg::SubscriptionIdsForActiveSubscriptions { start_after, limit } => encode_binary(
             &query_subscription_ids_for_active_subscriptions(deps, env, start_after, limit)?,
         ),
+        QueryMsg::Offering {
+            nft_address,
+            token_id,
+        } => encode_binary(&query_offering(deps, nft_address, token_id)?),
+        QueryMsg::Offerings { start_after, limit } => {
+            encode_binary(&query_offerings(deps, start_after, limit)?)
+        }
+        QueryMsg::OfferingsByCreator {
+            creator,
+            start_after,
+            limit,
+        } => encode_binary(&query_offerings_by_creator(
+            deps,
+            creator,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::SimulatePrice {
+            offering,
+            coupon,
+            periods,
+        } => encode_binary(&query_simulate_price(deps, env, offering, coupon, periods)?),
+        QueryMsg::ClaimableRevenue {
+            creator,
+            start_after,
+            limit,
+        } => encode_binary(&query_claimable_revenue(deps, creator, start_after, limit)?),
+        QueryMsg::SubscriberBalances {
+            subscriber,
+            start_after,
+            limit,
+        } => encode_binary(&query_subscriber_balances(
+            deps,
+            subscriber,
+            start_after,
+            limit,
+        )?),
         QueryMsg::AuthorizedAddresses {
             action,
             start_after,
@@ -486,10 +2462,11 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
 pub fn query_subscription(
     deps: Deps,
     creator: String,
+    token_id: String,
     env: Env,
     subscriber: String,
 ) -> Result<SubscriptionState, ContractError> {
-    let key = (creator.clone(), subscriber.clone());
+    let key = (creator.clone(), token_id, subscriber.clone());
     let mut subscription =
         subscriptions()
             .may_load(deps.storage, key.clone())?
@@ -500,41 +2477,71 @@ pub fn query_subscription(
                 ),
             })?;
 
-    // Evaluate and potentially update the subscription's `is_active` field
+    // Report the current status rather than the stored one
+    evaluate_subscription_status(&mut subscription, &env);
+
+    Ok(subscription)
+}
+
+pub fn query_subscription_by_id(
+    deps: Deps,
+    env: Env,
+    id: Uint128,
+) -> Result<SubscriptionState, ContractError> {
+    let mut subscription = load_subscription_by_id(deps.storage, id)?;
+
+    // Report the current status rather than the stored one
     evaluate_subscription_status(&mut subscription, &env);
 
     Ok(subscription)
 }
 
+pub fn query_has_access(
+    deps: Deps,
+    env: Env,
+    subscriber: String,
+    offering: Uint128,
+    at_time: Option<Timestamp>,
+) -> Result<HasAccessResponse, ContractError> {
+    // Past changes of status are not recorded, so only the present and future can be checked
+    let time = at_time.unwrap_or(env.block.time);
+    ensure!(
+        time >= env.block.time,
+        ContractError::CustomError {
+            msg: "Access can only be checked at the current block time or later.".to_string(),
+        }
+    );
+
+    let Some((_, subscription)) = find_subscriber_subscription(deps.storage, subscriber, offering)?
+    else {
+        return Ok(HasAccessResponse {
+            has_access: false,
+            expires_at: None,
+            status: None,
+        });
+    };
+
+    let status = subscription.status_at(time);
+    Ok(HasAccessResponse {
+        has_access: status.has_access(),
+        expires_at: subscription
+            .access_ends_at()
+            .filter(|_| status.has_access()),
+        status: Some(status),
+    })
+}
+
 pub fn query_subscriptions_for_creator(
     deps: Deps,
     creator: String,
     env: Env,
-    start_after: Option<(String, String)>,
+    start_after: Option<(String, String, String)>,
     limit: Option<u64>,
 ) -> Result<Vec<SubscriptionState>, ContractError> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-
-    // Convert `start_after` into `Bound` if provided
-    let start = start_after.map(Bound::exclusive);
-
-    let subscriptions = subscriptions()
-        .keys(deps.storage, start, None, Order::Ascending)
-        .filter_map(|res| {
-            let key = res.ok()?; // Ensure key exists and is valid
-            if key.0 == creator {
-                Some(key)
-            } else {
-                None
-            }
-        })
-        .take(limit)
-        .filter_map(|key| {
-            let mut subscription = subscriptions().may_load(deps.storage, key).ok().flatten()?;
-            evaluate_subscription_status(&mut subscription, &env); // Evaluate `is_active`
-            Some(subscription)
-        })
-        .collect();
+    let mut subscriptions = read_subscriptions(deps.storage, creator, start_after, limit)?;
+    for subscription in subscriptions.iter_mut() {
+        evaluate_subscription_status(subscription, &env); // Evaluate `status`
+    }
 
     Ok(subscriptions)
 }
@@ -543,30 +2550,14 @@ pub fn query_subscriptions_for_subscriber(
     deps: Deps,
     subscriber: String,
     env: Env,
-    start_after: Option<(String, String)>,
+    start_after: Option<(String, String, String)>,
     limit: Option<u64>,
 ) -> Result<Vec<SubscriptionState>, ContractError> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-
-    let start = start_after.map(Bound::exclusive);
-
-    let subscriptions = subscriptions()
-        .keys(deps.storage, start, None, Order::Ascending)
-        .filter_map(|res| {
-            let key = res.ok()?; // Ensure key exists and is valid
-            if key.1 == subscriber {
-                Some(key)
-            } else {
-                None
-            }
-        })
-        .take(limit)
-        .filter_map(|key| {
-            let mut subscription = subscriptions().may_load(deps.storage, key).ok().flatten()?;
-            evaluate_subscription_status(&mut subscription, &env); // Evaluate `is_active`
-            Some(subscription)
-        })
-        .collect();
+    let mut subscriptions =
+        read_subscriber_subscriptions(deps.storage, subscriber, start_after, limit)?;
+    for subscription in subscriptions.iter_mut() {
+        evaluate_subscription_status(subscription, &env); // Evaluate `status`
+    }
 
     Ok(subscriptions)
 }
@@ -574,93 +2565,199 @@ pub fn query_subscriptions_for_subscriber(
 pub fn query_subscription_ids_for_creator(
     deps: Deps,
     creator: String,
+    start_after: Option<(String, String, String)>,
+    limit: Option<u64>,
+) -> Result<Vec<Uint128>, ContractError> {
+    let subscription_ids = read_subscriptions(deps.storage, creator, start_after, limit)?
+        .into_iter()
+        .map(|subscription| subscription.subscription_id)
+        .collect();
+
+    Ok(subscription_ids)
+}
+
+pub fn query_subscription_ids_for_subscriber(
+    deps: Deps,
+    subscriber: String,
+    start_after: Option<(String, String, String)>,
+    limit: Option<u64>,
+) -> Result<Vec<Uint128>, ContractError> {
+    let subscription_ids =
+        read_subscriber_subscriptions(deps.storage, subscriber, start_after, limit)?
+            .into_iter()
+            .map(|subscription| subscription.subscription_id)
+            .collect();
+
+    Ok(subscription_ids)
+}
+
+/// Lists the IDs of subscriptions with access, ordered by when their access ends. Only
+/// subscriptions whose access ends from now on are read, so the cost grows with the page size.
+/// If the subscription at `start_after` no longer exists, the listing starts over.
+pub fn query_subscription_ids_for_active_subscriptions(
+    deps: Deps,
     env: Env,
-    start_after: Option<(String, String)>,
+    start_after: Option<(String, String, String)>,
     limit: Option<u64>,
 ) -> Result<Vec<Uint128>, ContractError> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let now = env.block.time.nanos();
 
-    let start = start_after.map(Bound::exclusive);
+    // Resume after the given subscription's position in the access end index
+    let resume_at = match start_after {
+        Some(key) => subscriptions()
+            .may_load(deps.storage, key.clone())?
+            .map(|subscription| (access_end_key(&subscription), key)),
+        None => None,
+    };
+    let start = match resume_at {
+        Some((access_end, key)) if access_end >= now => Bound::exclusive((access_end, key)),
+        _ => Bound::inclusive((now, (String::new(), String::new(), String::new()))),
+    };
 
     let subscription_ids = subscriptions()
-        .keys(deps.storage, start, None, Order::Ascending)
-        .filter_map(|res| {
-            let key = res.ok()?;
-            if key.0 == creator {
-                Some(key)
-            } else {
-                None
-            }
-        })
+        .idx
+        .access_end
+        .range(deps.storage, Some(start), None, Order::Ascending)
         .take(limit)
-        .filter_map(|key| {
-            let mut subscription = subscriptions().may_load(deps.storage, key).ok().flatten()?;
-            evaluate_subscription_status(&mut subscription, &env); // Evaluate `is_active`
-            Some(subscription.subscription_id)
-        })
-        .collect();
+        .map(|res| res.map(|(_, subscription)| subscription.subscription_id))
+        .collect::<StdResult<Vec<_>>>()?;
 
     Ok(subscription_ids)
 }
 
-pub fn query_subscription_ids_for_subscriber(
+pub fn query_offering(
+    deps: Deps,
+    nft_address: String,
+    token_id: String,
+) -> Result<Offering, ContractError> {
+    load_offering(deps.storage, &nft_address, &token_id)
+}
+
+pub fn query_simulate_price(
     deps: Deps,
-    subscriber: String,
     env: Env,
+    offering_id: Uint128,
+    coupon: Option<String>,
+    periods: Option<u64>,
+) -> Result<SimulatePriceResponse, ContractError> {
+    let (_, offering) = offerings()
+        .idx
+        .offering_id
+        .item(deps.storage, offering_id.u128())?
+        .ok_or(ContractError::CustomError {
+            msg: format!("No subscription offering found with ID {}.", offering_id),
+        })?;
+
+    let max_periods = offering.max_periods.max(1);
+    let periods = periods.unwrap_or(1);
+    ensure!(
+        (1..=max_periods).contains(&periods),
+        ContractError::CustomError {
+            msg: format!("Periods must be between 1 and {}.", max_periods),
+        }
+    );
+
+    let coupon = coupon
+        .map(|code| load_redeemable_coupon(deps.storage, &env, offering_id, &code, None))
+        .transpose()?;
+    let price = price_for_periods(
+        offering.payment_amount,
+        offering.multi_period_discount,
+        coupon.as_ref().map(|(_, coupon)| &coupon.discount),
+        periods,
+    )?;
+
+    Ok(SimulatePriceResponse {
+        price,
+        asset: offering.payment_asset,
+    })
+}
+
+pub fn query_offerings(
+    deps: Deps,
     start_after: Option<(String, String)>,
     limit: Option<u64>,
-) -> Result<Vec<Uint128>, ContractError> {
+) -> Result<Vec<Offering>, ContractError> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
 
     let start = start_after.map(Bound::exclusive);
 
-    let subscription_ids = subscriptions()
-        .keys(deps.storage, start, None, Order::Ascending)
-        .filter_map(|res| {
-            let key = res.ok()?;
-            if key.1 == subscriber {
-                Some(key)
-            } else {
-                None
-            }
-        })
+    let offerings = offerings()
+        .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
-        .filter_map(|key| {
-            let mut subscription = subscriptions().may_load(deps.storage, key).ok().flatten()?;
-            evaluate_subscription_status(&mut subscription, &env); // Evaluate `is_active`
-            Some(subscription.subscription_id)
-        })
-        .collect();
+        .map(|res| res.map(|(_, offering)| offering))
+        .collect::<StdResult<Vec<Offering>>>()?;
 
-    Ok(subscription_ids)
+    Ok(offerings)
 }
 
-pub fn query_subscription_ids_for_active_subscriptions(
+pub fn query_offerings_by_creator(
     deps: Deps,
-    env: Env,
+    creator: String,
     start_after: Option<(String, String)>,
     limit: Option<u64>,
-) -> Result<Vec<Uint128>, ContractError> {
+) -> Result<Vec<Offering>, ContractError> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
 
     let start = start_after.map(Bound::exclusive);
 
-    let subscription_ids = subscriptions()
-        .keys(deps.storage, start, None, Order::Ascending)
-        .filter_map(|res| {
-            let key = res.ok()?;
-            let mut subscription = subscriptions().may_load(deps.storage, key).ok().flatten()?;
-            evaluate_subscription_status(&mut subscription, &env); // Evaluate `is_active`
-            if subscription.is_active {
-                Some(subscription.subscription_id)
-            } else {
-                None
-            }
-        })
+    let offerings = offerings()
+        .idx
+        .creator
+        .prefix(creator)
+        .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
-        .collect();
+        .map(|res| res.map(|(_, offering)| offering))
+        .collect::<StdResult<Vec<Offering>>>()?;
 
-    Ok(subscription_ids)
+    Ok(offerings)
+}
+
+pub fn query_claimable_revenue(
+    deps: Deps,
+    creator: String,
+    start_after: Option<Asset>,
+    limit: Option<u64>,
+) -> Result<Vec<AssetBalance>, ContractError> {
+    read_balances(deps, &CREATOR_REVENUE, creator, start_after, limit)
+}
+
+pub fn query_subscriber_balances(
+    deps: Deps,
+    subscriber: String,
+    start_after: Option<Asset>,
+    limit: Option<u64>,
+) -> Result<Vec<AssetBalance>, ContractError> {
+    read_balances(deps, &SUBSCRIBER_BALANCES, subscriber, start_after, limit)
+}
+
+/// Lists an owner's balances in an (owner, asset) ledger
+fn read_balances(
+    deps: Deps,
+    balances: &Map<(String, String), Uint128>,
+    owner: String,
+    start_after: Option<Asset>,
+    limit: Option<u64>,
+) -> Result<Vec<AssetBalance>, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = start_after.map(|asset| Bound::exclusive(asset.to_string()));
+
+    let balances = balances
+        .prefix(owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| -> Result<AssetBalance, ContractError> {
+            let (asset, amount) = res?;
+            Ok(AssetBalance {
+                asset: asset.parse()?,
+                amount,
+            })
+        })
+        .collect::<Result<Vec<AssetBalance>, ContractError>>()?;
+
+    Ok(balances)
 }
 
 fn query_authorized_addresses(
@@ -681,12 +2778,14 @@ fn query_authorized_addresses(
 }
 
 fn evaluate_subscription_status(subscription: &mut SubscriptionState, env: &Env) {
-    if subscription.is_active {
-        if let Expiration::AtTime(end_time) = subscription.end_time {
-            if env.block.time > end_time {
-                subscription.is_active = false; // Mark as inactive
-                subscription.payment_pending = subscription.payment_amount;
-            }
-        }
+    let status = subscription.status_at(env.block.time);
+    if status != subscription.status
+        && matches!(
+            status,
+            SubscriptionStatus::PastDue | SubscriptionStatus::Expired
+        )
+    {
+        subscription.payment_pending = subscription.payment_amount;
     }
+    subscription.status = status;
 }
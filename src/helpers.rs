@@ -2,46 +2,222 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{
-    to_json_binary, Addr, CosmosMsg, CustomQuery, Querier, QuerierWrapper, StdResult, WasmMsg,
-    WasmQuery,
+    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, QuerierWrapper, StdResult, Timestamp,
+    Uint128, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 
-use crate::msg::{ExecuteMsg, GetCountResponse, QueryMsg};
+use crate::{
+    state::{Offering, SubscriptionState},
+    subscription::{Cw20HookMsg, ExecuteMsg, HasAccessResponse, QueryMsg},
+};
 
-/// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
-/// for working with this.
+/// SubscriptionContract is a wrapper around the address of a subscription contract that
+/// builds its messages and queries for other contracts and clients
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct CwTemplateContract(pub Addr);
+pub struct SubscriptionContract(pub Addr);
 
-impl CwTemplateContract {
+impl SubscriptionContract {
     pub fn addr(&self) -> Addr {
         self.0.clone()
     }
 
-    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+    /// Executes `msg` on the subscription contract with `funds` attached
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
         let msg = to_json_binary(&msg.into())?;
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
             msg,
-            funds: vec![],
+            funds,
         }
         .into())
     }
 
-    /// Get Count
-    pub fn count<Q, T, CQ>(&self, querier: &Q) -> StdResult<GetCountResponse>
-    where
-        Q: Querier,
-        T: Into<String>,
-        CQ: CustomQuery,
-    {
-        let msg = QueryMsg::GetCount {};
-        let query = WasmQuery::Smart {
-            contract_addr: self.addr().into(),
-            msg: to_json_binary(&msg)?,
+    /// Sends `amount` of the CW20 token at `cw20_address` to the subscription contract with `hook`
+    pub fn send_cw20(
+        &self,
+        cw20_address: impl Into<String>,
+        amount: Uint128,
+        hook: &Cw20HookMsg,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: cw20_address.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: self.addr().into(),
+                amount,
+                msg: to_json_binary(hook)?,
+            })?,
+            funds: vec![],
         }
-        .into();
-        let res: GetCountResponse = QuerierWrapper::<CQ>::new(querier).query(&query)?;
-        Ok(res)
+        .into())
+    }
+
+    /// Subscribes to an offering priced in a CW20 token, paying `amount` of it
+    pub fn subscribe_cw20(
+        &self,
+        cw20_address: impl Into<String>,
+        amount: Uint128,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.send_cw20(
+            cw20_address,
+            amount,
+            &Cw20HookMsg::Subscribe {
+                token_id: token_id.into(),
+                nft_address: nft_address.into(),
+                coupon: None,
+            },
+        )
+    }
+
+    /// Subscribes to an offering priced in a native denom, paying with `funds`
+    pub fn subscribe_native(
+        &self,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::Subscribe {
+                token_id: token_id.into(),
+                nft_address: nft_address.into(),
+                coupon: None,
+            },
+            funds,
+        )
+    }
+
+    /// Starts the free trial of an offering
+    pub fn start_trial(
+        &self,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::StartTrial {
+                token_id: token_id.into(),
+                nft_address: nft_address.into(),
+            },
+            vec![],
+        )
+    }
+
+    /// Renews a subscription paid in a CW20 token, paying `amount` of it
+    pub fn renew_cw20(
+        &self,
+        cw20_address: impl Into<String>,
+        amount: Uint128,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.send_cw20(
+            cw20_address,
+            amount,
+            &Cw20HookMsg::Renew {
+                token_id: Some(token_id.into()),
+                nft_address: Some(nft_address.into()),
+                subscription_id: None,
+                coupon: None,
+            },
+        )
+    }
+
+    /// Renews a subscription paid in a native denom, paying with `funds`
+    pub fn renew_native(
+        &self,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::Renew {
+                token_id: Some(token_id.into()),
+                nft_address: Some(nft_address.into()),
+                subscription_id: None,
+                coupon: None,
+            },
+            funds,
+        )
+    }
+
+    /// Cancels the sender's subscription, right away or at the end of the current period
+    pub fn cancel(
+        &self,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+        immediate: bool,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::Cancel {
+                nft_address: Some(nft_address.into()),
+                token_id: Some(token_id.into()),
+                subscription_id: None,
+                immediate,
+            },
+            vec![],
+        )
+    }
+
+    /// Get a subscription by its NFT tier and subscriber
+    pub fn subscription<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+        subscriber: impl Into<String>,
+    ) -> StdResult<SubscriptionState> {
+        querier.query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Subscription {
+                creator: nft_address.into(),
+                token_id: token_id.into(),
+                subscriber: subscriber.into(),
+            },
+        )
+    }
+
+    /// Get a subscription by its subscription ID
+    pub fn subscription_by_id<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        id: Uint128,
+    ) -> StdResult<SubscriptionState> {
+        querier.query_wasm_smart(self.addr(), &QueryMsg::SubscriptionById { id })
+    }
+
+    /// Get the offering listed for an NFT tier
+    pub fn offering<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        nft_address: impl Into<String>,
+        token_id: impl Into<String>,
+    ) -> StdResult<Offering> {
+        querier.query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Offering {
+                nft_address: nft_address.into(),
+                token_id: token_id.into(),
+            },
+        )
+    }
+
+    /// Check whether `subscriber` has access to `offering`, for contracts gating their features
+    /// behind a subscription
+    pub fn has_access<C: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<C>,
+        subscriber: impl Into<String>,
+        offering: Uint128,
+        at_time: Option<Timestamp>,
+    ) -> StdResult<HasAccessResponse> {
+        querier.query_wasm_smart(
+            self.addr(),
+            &QueryMsg::HasAccess {
+                subscriber: subscriber.into(),
+                offering,
+                at_time,
+            },
+        )
     }
 }
@@ -1,7 +1,6 @@
 pub mod contract;
 mod error;
 pub mod helpers;
-pub mod msg;
 pub mod state;
 pub mod subscription;
 
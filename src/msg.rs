@@ -1,30 +0,0 @@
-use andromeda_std::{andr_exec, andr_instantiate, andr_query};
-use cosmwasm_schema::{cw_serde, QueryResponses};
-
-#[andr_instantiate]
-#[cw_serde]
-pub struct InstantiateMsg {
-    pub count: i32,
-}
-
-#[andr_exec]
-#[cw_serde]
-pub enum ExecuteMsg {
-    Increment {},
-    Reset { count: i32 },
-}
-
-#[andr_query]
-#[cw_serde]
-#[derive(QueryResponses)]
-pub enum QueryMsg {
-    // GetCount returns the current count as a json-encoded number
-    #[returns(GetCountResponse)]
-    GetCount {},
-}
-
-// We define a custom struct for each query response
-#[cw_serde]
-pub struct GetCountResponse {
-    pub count: i32,
-}
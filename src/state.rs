@@ -1,8 +1,16 @@
-use andromeda_std::error::ContractError;
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use andromeda_std::{amp::Recipient, error::ContractError};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Order, Storage, Uint128};
-use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, MultiIndex};
+use cosmwasm_std::{
+    coins, ensure, to_json_binary, Addr, BankMsg, CosmosMsg, Decimal, HexBinary, Order, StdError,
+    Storage, Timestamp, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
 use cw_utils::Expiration;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Constants for pagination limits
 const MAX_LIMIT: u64 = 30;
@@ -11,74 +19,575 @@ const DEFAULT_LIMIT: u64 = 10;
 /// Tracks the next available subscription ID
 pub const NEXT_SUBSCRIPTION_ID: Item<Uint128> = Item::new("next_subscription_id");
 
+/// Revenue collected on behalf of creators and not yet withdrawn, keyed by (creator, asset)
+pub const CREATOR_REVENUE: Map<(String, String), Uint128> = Map::new("creator_revenue");
+
+/// Time each (subscriber, offering ID) claimed the offering's free trial, as a trial can only
+/// be claimed once per offering
+pub const CLAIMED_TRIALS: Map<(String, u128), Timestamp> = Map::new("claimed_trials");
+
+/// Coupons created by offering creators, keyed by (offering ID, code hash), see `coupon_code_hash`
+pub const COUPONS: Map<(u128, String), Coupon> = Map::new("coupons");
+
+/// Number of times each subscriber redeemed a coupon, keyed by (offering ID, code hash, subscriber)
+pub const COUPON_REDEMPTIONS: Map<(u128, String, String), u64> = Map::new("coupon_redemptions");
+
+/// Andromeda CW721 ADO that membership tokens are minted from, if enabled
+pub const MEMBERSHIP_NFT: Item<Addr> = Item::new("membership_nft");
+
+/// Prepaid balances deposited by subscribers to cover renewals, keyed by (subscriber, asset)
+pub const SUBSCRIBER_BALANCES: Map<(String, String), Uint128> = Map::new("subscriber_balances");
+
+/// Asset used to pay for a subscription
+#[cw_serde]
+pub enum Asset {
+    /// Native bank denom, e.g. `uandr` or an IBC denom
+    NativeToken(String),
+    /// CW20 token contract address
+    Cw20Token(String),
+}
+
+impl Asset {
+    /// Builds the message sending `amount` of this asset to `recipient`
+    pub fn transfer_msg(
+        &self,
+        recipient: &Addr,
+        amount: Uint128,
+    ) -> Result<CosmosMsg, ContractError> {
+        let msg = match self {
+            Asset::NativeToken(denom) => BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins(amount.u128(), denom),
+            }
+            .into(),
+            Asset::Cw20Token(contract_addr) => WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        };
+        Ok(msg)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Asset::NativeToken(denom) => write!(f, "native:{}", denom),
+            Asset::Cw20Token(contract_addr) => write!(f, "cw20:{}", contract_addr),
+        }
+    }
+}
+
+impl FromStr for Asset {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("native", denom)) => Ok(Asset::NativeToken(denom.to_string())),
+            Some(("cw20", contract_addr)) => Ok(Asset::Cw20Token(contract_addr.to_string())),
+            _ => Err(StdError::parse_err(
+                "Asset",
+                format!("Invalid asset: {}", s),
+            )),
+        }
+    }
+}
+
+/// Refund paid out to a subscriber when they cancel
+#[cw_serde]
+pub enum RefundPolicy {
+    /// Cancelling never refunds the current period
+    NoRefund,
+    /// Refunds the unused part of the current period, prorated by remaining seconds
+    Prorated,
+    /// Refunds the full period when cancelled within `window_seconds` of its start
+    CoolingOff { window_seconds: u64 },
+}
+
+/// Subscription offering registered by a creator for an NFT tier
+#[cw_serde]
+pub struct Offering {
+    pub offering_id: Uint128,                   // Unique offering ID
+    pub creator: String,                        // Address of the content creator
+    pub nft_address: String,                    // NFT contract address
+    pub token_id: String,                       // NFT token ID of the offering tier
+    pub payment_amount: Uint128,                // Price of one period
+    pub payment_asset: Asset,                   // Asset accepted as payment
+    pub subscription_duration: u64,             // Length of one period in seconds
+    pub metadata: Option<String>,               // Offering description set by the creator
+    pub refund_policy: RefundPolicy,            // Refund paid out on cancellation
+    pub max_periods: u64, // Maximum number of periods that can be paid for up front
+    pub multi_period_discount: Option<Decimal>, // Discount applied when paying for several periods
+    pub grace_period_seconds: u64, // Time after the end of a period during which access is kept
+    pub trial_duration: u64, // Length of the free trial in seconds, 0 if the offering has none
+    pub transferable: bool, // Whether subscribers can transfer their subscription to another address
+    pub revenue_split: Vec<RevenueShare>, // Recipients of the revenue by weight, all to the creator if empty
+    pub payout: Payout,                   // Whether payments are held for withdrawal or forwarded
+    pub subscription_count: u64,          // Number of subscriptions created for this offering
+    pub refundable_until: Timestamp, // Time until which a term sold with a refund policy can be refunded
+}
+
+impl Offering {
+    /// Whether payments are forwarded away from the creator's unclaimed revenue instead of held in it
+    pub fn forwards_revenue(&self) -> bool {
+        self.payout != Payout::Custody || !self.revenue_split.is_empty()
+    }
+
+    /// Extends `refundable_until` to cover the refunds `subscription` can claim for its term
+    pub fn record_refundable_term(&mut self, subscription: &SubscriptionState) {
+        if let Some(refundable_until) = subscription.refundable_until() {
+            self.refundable_until = self.refundable_until.max(refundable_until);
+        }
+    }
+}
+
+/// Changes to the terms of an offering made with `UpdateOffering`, unset fields are kept
+#[derive(Default)]
+pub struct OfferingUpdate {
+    pub payment_amount: Option<Uint128>,
+    pub duration: Option<u64>,
+    pub metadata: Option<String>,
+    pub refund_policy: Option<RefundPolicy>,
+    pub max_periods: Option<u64>,
+    pub multi_period_discount: Option<Decimal>,
+    pub grace_period_seconds: Option<u64>,
+    pub trial_duration: Option<u64>,
+    pub transferable: Option<bool>,
+    pub revenue_split: Option<Vec<RevenueShare>>, // Replaces the revenue recipients
+    pub payout: Option<Payout>,
+}
+
+/// How the payments for an offering are paid out
+#[cw_serde]
+pub enum Payout {
+    /// Payments are held by the contract and credited to revenue balances for withdrawal
+    Custody,
+    /// Payments are forwarded to `recipient`, with its message if it has one, as they are made
+    Immediate { recipient: Recipient },
+}
+
+/// Share of an offering's revenue paid to a recipient
+#[cw_serde]
+pub struct RevenueShare {
+    pub recipient: Recipient, // Recipient of the share, with a message only if it is forwarded
+    pub weight: u64,          // Weight of the share relative to the other recipients
+    pub forward: bool, // Send the share with each payment instead of crediting it for withdrawal
+}
+
+/// Discount taken off the price of a subscription or renewal by a coupon
+#[cw_serde]
+pub enum CouponDiscount {
+    /// Takes a share of the price off, below 100%
+    Percent(Decimal),
+    /// Takes a fixed amount of the payment asset off
+    Fixed(Uint128),
+}
+
+impl CouponDiscount {
+    /// Applies the discount to `price`
+    pub fn apply(&self, price: Uint128) -> Uint128 {
+        match self {
+            CouponDiscount::Percent(percent) => price * (Decimal::one() - *percent),
+            CouponDiscount::Fixed(amount) => price.saturating_sub(*amount),
+        }
+    }
+}
+
+/// Promotional code created by a creator for one of their offerings
+#[cw_serde]
+pub struct Coupon {
+    pub discount: CouponDiscount,     // Discount taken off the price
+    pub max_redemptions: Option<u64>, // Total number of redemptions allowed, unlimited if not set
+    pub max_redemptions_per_address: Option<u64>, // Redemptions allowed per subscriber, unlimited if not set
+    pub valid_from: Option<Timestamp>,            // Time from which the coupon can be redeemed
+    pub valid_until: Option<Timestamp>, // Time after which the coupon can no longer be redeemed
+    pub redemptions: u64,               // Number of times the coupon has been redeemed
+}
+
+impl Coupon {
+    /// Returns true if the coupon is within its validity window at `time`
+    pub fn is_valid_at(&self, time: Timestamp) -> bool {
+        self.valid_from.map_or(true, |from| time >= from)
+            && self.valid_until.map_or(true, |until| time <= until)
+    }
+}
+
+/// Hash under which a coupon code is stored, so codes are not readable from contract state
+pub fn coupon_code_hash(code: &str) -> String {
+    HexBinary::from(Sha256::digest(code.as_bytes()).as_slice()).to_hex()
+}
+
 /// Stores the state of individual subscriptions
 #[cw_serde]
 pub struct SubscriptionState {
-    pub subscription_id: Uint128,   // Unique subscription ID
-    pub creator: String,            // Address of the content creator
-    pub subscriber: String,         // Address of the subscriber
-    pub token_id: String,           // NFT token ID
-    pub nft_address: String,        // NFT contract address
-    pub start_time: Expiration,     // Subscription start time
-    pub end_time: Expiration,       // Subscription end time
-    pub payment_amount: Uint128,    // Payment amount for subscription
-    pub payment_pending: Uint128,   // Payment amount pending for current for this subscription
-    pub payment_denom: String,      // Denomination of the payment (CW20 or native token)
+    pub subscription_id: Uint128,               // Unique subscription ID
+    pub offering_id: Uint128,                   // ID of the offering subscribed to
+    pub creator: String,                        // Address of the content creator
+    pub subscriber: String,                     // Address of the subscriber
+    pub token_id: String,                       // NFT token ID
+    pub nft_address: String,                    // NFT contract address
+    pub start_time: Expiration,                 // Subscription start time
+    pub end_time: Expiration,                   // Subscription end time
+    pub payment_amount: Uint128,                // Payment amount for subscription
+    pub payment_pending: Uint128, // Payment amount pending for current for this subscription
+    pub payment_asset: Asset,     // Asset accepted as payment for subscription
+    pub metadata: Option<String>, // Offering description set by the creator
+    pub refund_policy: RefundPolicy, // Refund paid out on cancellation
     pub subscription_duration: u64, // Default subscription duration in seconds (specified by creator)
-    pub is_active: bool,            // Tracks if the subscription is active
+    pub status: SubscriptionStatus, // Stored status, see `status_at` for the current status
+    pub auto_renew: bool, // Renew from the subscriber's CW20 allowance when the period ends
+    pub max_periods: u64, // Maximum number of periods that can be paid for up front
+    pub multi_period_discount: Option<Decimal>, // Discount applied when paying for several periods
+    pub amount_paid: Uint128, // Amount paid for the current term
+    pub amount_credited: Uint128, // Part of `amount_paid` credited to the creator after platform fees
+    pub grace_period_seconds: u64, // Time after the end of a period during which access is kept
+    pub membership_token_id: Option<String>, // Membership token minted to the subscriber, if any
+}
+
+impl SubscriptionState {
+    /// Status of the subscription at `time`. The stored status only changes on execution, so a
+    /// subscription whose paid period has since ended is reported as past due or ended here.
+    /// Earlier changes of status are not recorded, so `time` must not be before the current block.
+    pub fn status_at(&self, time: Timestamp) -> SubscriptionStatus {
+        let end_time = match self.end_time {
+            Expiration::AtTime(end_time) if time > end_time => end_time,
+            _ => return self.status,
+        };
+        match self.status {
+            SubscriptionStatus::Active | SubscriptionStatus::PastDue => {
+                if time <= self.grace_period_end(end_time) {
+                    SubscriptionStatus::PastDue
+                } else {
+                    SubscriptionStatus::Expired
+                }
+            }
+            SubscriptionStatus::CancelScheduled => SubscriptionStatus::Cancelled,
+            SubscriptionStatus::Trialing => SubscriptionStatus::Expired,
+            status => status,
+        }
+    }
+
+    /// Time at which access ends unless the subscription is renewed, including the grace period.
+    /// Returns `None` if the stored status does not grant access.
+    pub fn access_ends_at(&self) -> Option<Timestamp> {
+        let end_time = match self.end_time {
+            Expiration::AtTime(end_time) => end_time,
+            _ => return None,
+        };
+        match self.status {
+            SubscriptionStatus::Active | SubscriptionStatus::PastDue => {
+                Some(self.grace_period_end(end_time))
+            }
+            SubscriptionStatus::Trialing | SubscriptionStatus::CancelScheduled => Some(end_time),
+            _ => None,
+        }
+    }
+
+    /// Replaces the terms the subscription was sold with by the current terms of `offering`
+    pub fn apply_offering_terms(&mut self, offering: &Offering) {
+        self.payment_amount = offering.payment_amount;
+        self.payment_asset = offering.payment_asset.clone();
+        self.metadata = offering.metadata.clone();
+        self.refund_policy = offering.refund_policy.clone();
+        self.subscription_duration = offering.subscription_duration;
+        self.max_periods = offering.max_periods;
+        self.multi_period_discount = offering.multi_period_discount;
+        self.grace_period_seconds = offering.grace_period_seconds;
+    }
+
+    /// Time until which a refund can be claimed for the current term, `None` if it cannot be
+    /// refunded
+    pub fn refundable_until(&self) -> Option<Timestamp> {
+        let (start_time, end_time) = match (self.start_time, self.end_time) {
+            (Expiration::AtTime(start_time), Expiration::AtTime(end_time)) => {
+                (start_time, end_time)
+            }
+            _ => return None,
+        };
+        if self.amount_credited.is_zero() {
+            return None;
+        }
+        match self.refund_policy {
+            RefundPolicy::NoRefund => None,
+            RefundPolicy::Prorated => Some(end_time),
+            RefundPolicy::CoolingOff { window_seconds } => Some(Timestamp::from_seconds(
+                start_time.seconds().saturating_add(window_seconds),
+            )),
+        }
+    }
+
+    /// End of the grace period following a paid period ending at `end_time`. Saturates instead of
+    /// panicking so a record stored with an oversized grace period cannot block the renewal crank.
+    pub fn grace_period_end(&self, end_time: Timestamp) -> Timestamp {
+        Timestamp::from_nanos(
+            end_time
+                .nanos()
+                .saturating_add(self.grace_period_seconds.saturating_mul(1_000_000_000)),
+        )
+    }
+}
+
+/// Lifecycle status of a subscription
+#[cw_serde]
+#[derive(Copy)]
+pub enum SubscriptionStatus {
+    /// A free trial is in progress, renewing converts it to a paid subscription
+    Trialing,
+    /// The paid period is in progress
+    Active,
+    /// The paid period ended without renewal, access is kept until the grace period ends
+    PastDue,
+    /// Access is kept until the end of the paid period, after which the subscription is cancelled
+    CancelScheduled,
+    /// Cancelled by the subscriber
+    Cancelled,
+    /// The paid and grace periods ended without renewal
+    Expired,
+}
+
+impl SubscriptionStatus {
+    /// Returns true if the subscriber currently has access
+    pub fn has_access(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStatus::Trialing
+                | SubscriptionStatus::Active
+                | SubscriptionStatus::PastDue
+                | SubscriptionStatus::CancelScheduled
+        )
+    }
+
+    /// Validates a change of status, returning the new status
+    pub fn transition(self, to: SubscriptionStatus) -> Result<SubscriptionStatus, ContractError> {
+        use SubscriptionStatus::*;
+
+        let allowed = match (self, to) {
+            // Renewing restores access from any subscription status
+            (Trialing | Active | PastDue | CancelScheduled | Cancelled | Expired, Active) => true,
+            (Trialing, CancelScheduled) => true,
+            (Active, PastDue | CancelScheduled) => true,
+            (Trialing | Active | PastDue | CancelScheduled, Cancelled) => true,
+            (Trialing | Active | PastDue | CancelScheduled, Expired) => true,
+            _ => false,
+        };
+        ensure!(
+            allowed,
+            ContractError::CustomError {
+                msg: format!(
+                    "Invalid subscription status transition from {} to {}.",
+                    self, to
+                ),
+            }
+        );
+        Ok(to)
+    }
+}
+
+impl fmt::Display for SubscriptionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            SubscriptionStatus::Trialing => "trialing",
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::PastDue => "past_due",
+            SubscriptionStatus::CancelScheduled => "cancel_scheduled",
+            SubscriptionStatus::Cancelled => "cancelled",
+            SubscriptionStatus::Expired => "expired",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// Index structure for offerings
+pub struct OfferingIndices<'a> {
+    /// Secondary index: offerings by creator address
+    pub creator: MultiIndex<'a, String, Offering, (String, String)>,
+    /// Unique index: offerings by offering ID
+    pub offering_id: UniqueIndex<'a, u128, Offering, (String, String)>,
+}
+
+impl IndexList<Offering> for OfferingIndices<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Offering>> + '_> {
+        let v: Vec<&dyn Index<Offering>> = vec![&self.creator, &self.offering_id];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Indexed map to store offerings, keyed by (nft_address, token_id)
+pub fn offerings<'a>() -> IndexedMap<'a, (String, String), Offering, OfferingIndices<'a>> {
+    let indices = OfferingIndices {
+        creator: MultiIndex::new(
+            |_pk, offering| offering.creator.clone(),
+            "offerings",
+            "offerings__creator",
+        ),
+        offering_id: UniqueIndex::new(|offering| offering.offering_id.u128(), "offerings__id"),
+    };
+    IndexedMap::new("offerings", indices)
 }
 
 /// Index structure for subscriptions
 pub struct SubscriptionIndices<'a> {
     /// Secondary index: subscriptions by creator address
-    pub creator: MultiIndex<'a, String, SubscriptionState, (String, String)>,
+    pub creator: MultiIndex<'a, String, SubscriptionState, (String, String, String)>,
+    /// Secondary index: subscriptions by subscriber address
+    pub subscriber: MultiIndex<'a, String, SubscriptionState, (String, String, String)>,
+    /// Secondary index: subscriptions ordered by when their access ends, see `access_end_key`
+    pub access_end: MultiIndex<'a, u64, SubscriptionState, (String, String, String)>,
+    /// Secondary index: subscriptions by (subscriber, offering ID)
+    pub subscriber_offering:
+        MultiIndex<'a, (String, u128), SubscriptionState, (String, String, String)>,
+    /// Secondary index: subscriptions ordered by when `ProcessRenewals` is due, see `renewal_key`
+    pub renewal: MultiIndex<'a, u64, SubscriptionState, (String, String, String)>,
+    /// Unique index: subscriptions by subscription ID
+    pub subscription_id: UniqueIndex<'a, u128, SubscriptionState, (String, String, String)>,
 }
 
 /// Implementing indices for subscriptions
 impl IndexList<SubscriptionState> for SubscriptionIndices<'_> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<SubscriptionState>> + '_> {
-        let v: Vec<&dyn Index<SubscriptionState>> = vec![&self.creator];
+        let v: Vec<&dyn Index<SubscriptionState>> = vec![
+            &self.creator,
+            &self.subscriber,
+            &self.access_end,
+            &self.subscriber_offering,
+            &self.renewal,
+            &self.subscription_id,
+        ];
         Box::new(v.into_iter())
     }
 }
 
-/// Indexed map to store subscriptions and their secondary indices
+/// Indexed map to store subscriptions and their secondary indices, keyed by
+/// (nft_address, token_id, subscriber)
 pub fn subscriptions<'a>(
-) -> IndexedMap<'a, (String, String), SubscriptionState, SubscriptionIndices<'a>> {
+) -> IndexedMap<'a, (String, String, String), SubscriptionState, SubscriptionIndices<'a>> {
     let indices = SubscriptionIndices {
         creator: MultiIndex::new(
             |_pk, subscription| subscription.creator.clone(),
             "subscriptions",
             "creator_index",
         ),
+        subscriber: MultiIndex::new(
+            |_pk, subscription| subscription.subscriber.clone(),
+            "subscriptions",
+            "subscriptions__subscriber",
+        ),
+        access_end: MultiIndex::new(
+            |_pk, subscription| access_end_key(subscription),
+            "subscriptions",
+            "subscriptions__access_end",
+        ),
+        subscriber_offering: MultiIndex::new(
+            |_pk, subscription| {
+                (
+                    subscription.subscriber.clone(),
+                    subscription.offering_id.u128(),
+                )
+            },
+            "subscriptions",
+            "subscriptions__subscriber_offering",
+        ),
+        renewal: MultiIndex::new(
+            |_pk, subscription| renewal_key(subscription),
+            "subscriptions",
+            "subscriptions__renewal",
+        ),
+        subscription_id: UniqueIndex::new(
+            |subscription| subscription.subscription_id.u128(),
+            "subscriptions__id",
+        ),
     };
     IndexedMap::new("subscriptions", indices)
 }
 
+/// Key of a subscription in the access end index, in nanoseconds: the subscription has access
+/// until the block time is past this key. Subscriptions whose stored status grants no access
+/// sort first, so listings of subscriptions with access never read them.
+pub fn access_end_key(subscription: &SubscriptionState) -> u64 {
+    subscription
+        .access_ends_at()
+        .map_or(0, |access_end| access_end.nanos())
+}
+
+/// Key of a subscription in the renewal index, in nanoseconds: `ProcessRenewals` has to act on
+/// it once the block time is past this key. Subscriptions the crank no longer acts on sort last,
+/// so it never reads them.
+pub fn renewal_key(subscription: &SubscriptionState) -> u64 {
+    match (subscription.status, subscription.end_time) {
+        (
+            SubscriptionStatus::Trialing | SubscriptionStatus::Active,
+            Expiration::AtTime(end_time),
+        ) => end_time.nanos(),
+        // Past due subscriptions are retried once their grace period ends
+        (SubscriptionStatus::PastDue, Expiration::AtTime(end_time)) => {
+            subscription.grace_period_end(end_time).nanos()
+        }
+        _ => u64::MAX,
+    }
+}
+
 /// Helper function to paginate and read subscriptions by creator
 pub fn read_subscriptions(
     storage: &dyn Storage,
     creator: String,
-    start_after: Option<(String, String)>,
+    start_after: Option<(String, String, String)>,
     limit: Option<u64>,
 ) -> Result<Vec<SubscriptionState>, ContractError> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
 
-    let keys = subscriptions()
+    let subscriptions = subscriptions()
         .idx
         .creator
         .prefix(creator)
-        .keys(storage, start, None, Order::Ascending)
+        .range(storage, start, None, Order::Ascending)
         .take(limit)
-        .collect::<Result<Vec<(String, String)>, _>>()?;
+        .map(|res| res.map(|(_, subscription)| subscription))
+        .collect::<Result<Vec<SubscriptionState>, _>>()?;
+    Ok(subscriptions)
+}
 
-    let mut res = Vec::new();
-    for key in keys {
-        let state = subscriptions().load(storage, key)?;
-        res.push(state);
-    }
-    Ok(res)
+/// Helper function to paginate and read subscriptions by subscriber
+pub fn read_subscriber_subscriptions(
+    storage: &dyn Storage,
+    subscriber: String,
+    start_after: Option<(String, String, String)>,
+    limit: Option<u64>,
+) -> Result<Vec<SubscriptionState>, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let subscriptions = subscriptions()
+        .idx
+        .subscriber
+        .prefix(subscriber)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|(_, subscription)| subscription))
+        .collect::<Result<Vec<SubscriptionState>, _>>()?;
+    Ok(subscriptions)
+}
+
+/// Helper function to find a subscriber's subscription to an offering, with its key
+pub fn find_subscriber_subscription(
+    storage: &dyn Storage,
+    subscriber: String,
+    offering_id: Uint128,
+) -> Result<Option<((String, String, String), SubscriptionState)>, ContractError> {
+    // A subscriber has a single subscription per offering
+    let subscription = subscriptions()
+        .idx
+        .subscriber_offering
+        .prefix((subscriber, offering_id.u128()))
+        .range(storage, None, None, Order::Ascending)
+        .next()
+        .transpose()?;
+    Ok(subscription)
 }
 
 /// Helper function to fetch and increment the next subscription ID
@@ -91,3 +600,212 @@ pub fn get_and_increment_next_subscription_id(
 
     Ok(next_id)
 }
+
+/// Helper function to credit an owner's balance in an (owner, asset) ledger
+pub fn credit_balance(
+    storage: &mut dyn Storage,
+    balances: &Map<(String, String), Uint128>,
+    owner: &str,
+    asset: &Asset,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    balances.update(
+        storage,
+        (owner.to_string(), asset.to_string()),
+        |balance| -> Result<Uint128, ContractError> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        },
+    )
+}
+
+/// Helper function to debit an owner's balance in an (owner, asset) ledger
+pub fn debit_balance(
+    storage: &mut dyn Storage,
+    balances: &Map<(String, String), Uint128>,
+    owner: &str,
+    asset: &Asset,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let key = (owner.to_string(), asset.to_string());
+    let remaining = balances
+        .may_load(storage, key.clone())?
+        .unwrap_or_default()
+        .checked_sub(amount)?;
+    if remaining.is_zero() {
+        balances.remove(storage, key);
+    } else {
+        balances.save(storage, key, &remaining)?;
+    }
+    Ok(())
+}
+
+/// Subscription record as stored by 0.1.0, keyed by (nft_address, subscriber). Offerings were
+/// stored as records with an empty subscriber.
+#[derive(Serialize, Deserialize, Clone)]
+struct LegacySubscriptionState {
+    subscription_id: Uint128,
+    creator: String,
+    subscriber: String,
+    token_id: String,
+    nft_address: String,
+    start_time: Expiration,
+    end_time: Expiration,
+    payment_amount: Uint128,
+    payment_pending: Uint128,
+    payment_denom: String,
+    subscription_duration: u64,
+    is_active: bool,
+}
+
+impl LegacySubscriptionState {
+    fn status(&self) -> SubscriptionStatus {
+        if self.is_active {
+            SubscriptionStatus::Active
+        } else if matches!(self.end_time, Expiration::Never {}) {
+            // Cancelling cleared the subscription period
+            SubscriptionStatus::Cancelled
+        } else {
+            SubscriptionStatus::Expired
+        }
+    }
+}
+
+/// Index structure for subscriptions stored by 0.1.0
+struct LegacySubscriptionIndices<'a> {
+    creator: MultiIndex<'a, String, LegacySubscriptionState, (String, String)>,
+}
+
+impl IndexList<LegacySubscriptionState> for LegacySubscriptionIndices<'_> {
+    fn get_indexes(
+        &'_ self,
+    ) -> Box<dyn Iterator<Item = &'_ dyn Index<LegacySubscriptionState>> + '_> {
+        let v: Vec<&dyn Index<LegacySubscriptionState>> = vec![&self.creator];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Indexed map of subscriptions as stored by 0.1.0
+fn legacy_subscriptions<'a>(
+) -> IndexedMap<'a, (String, String), LegacySubscriptionState, LegacySubscriptionIndices<'a>> {
+    let indices = LegacySubscriptionIndices {
+        creator: MultiIndex::new(
+            |_pk, subscription| subscription.creator.clone(),
+            "subscriptions",
+            "creator_index",
+        ),
+    };
+    IndexedMap::new("subscriptions", indices)
+}
+
+/// Rewrites the records stored by 0.1.0, so it must run before any record is stored in the
+/// current layout. Offerings, stored as subscriptions with an empty subscriber, move to
+/// `offerings()`. Subscriptions are re-keyed by (nft_address, token_id, subscriber) under the
+/// token of their offering and get a `SubscriptionStatus` and the ID of their offering (zero if
+/// it was not found). 0.1.0 did not record which CW20 was paid, so every record is bound to
+/// `payment_asset`, which is required if there are any. Returns the number of records migrated.
+pub fn migrate_subscriptions(
+    storage: &mut dyn Storage,
+    payment_asset: Option<Asset>,
+) -> Result<u64, ContractError> {
+    let records = legacy_subscriptions()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?;
+    if records.is_empty() {
+        return Ok(0);
+    }
+    let migrated = records.len() as u64;
+    let payment_asset = payment_asset.ok_or(ContractError::CustomError {
+        msg: "A legacy payment asset is required to migrate the records stored by 0.1.0."
+            .to_string(),
+    })?;
+
+    // Removing the records through the legacy map removes their creator index entries with them
+    for (key, _) in records.iter() {
+        legacy_subscriptions().remove(storage, key.clone())?;
+    }
+
+    let (offering_records, subscription_records): (Vec<_>, Vec<_>) = records
+        .into_iter()
+        .partition(|((_, subscriber), _)| subscriber.is_empty());
+
+    // 0.1.0 listed a single offering per NFT contract
+    let mut listed_tiers = BTreeMap::new();
+    for ((nft_address, _), record) in offering_records {
+        let subscription_count = subscription_records
+            .iter()
+            .filter(|((nft, _), _)| *nft == nft_address)
+            .count() as u64;
+        let offering = Offering {
+            offering_id: record.subscription_id,
+            creator: record.creator,
+            nft_address: nft_address.clone(),
+            token_id: record.token_id,
+            payment_amount: record.payment_amount,
+            payment_asset: payment_asset.clone(),
+            subscription_duration: record.subscription_duration,
+            metadata: None,
+            refund_policy: RefundPolicy::NoRefund,
+            max_periods: 1,
+            multi_period_discount: None,
+            grace_period_seconds: 0,
+            trial_duration: 0,
+            transferable: false,
+            revenue_split: vec![],
+            payout: Payout::Custody,
+            subscription_count,
+            refundable_until: Timestamp::from_nanos(0),
+        };
+        offerings().save(
+            storage,
+            (nft_address.clone(), offering.token_id.clone()),
+            &offering,
+        )?;
+        listed_tiers.insert(nft_address, (offering.token_id, offering.offering_id));
+    }
+
+    for ((nft_address, subscriber), record) in subscription_records {
+        let (token_id, offering_id) = listed_tiers
+            .get(&nft_address)
+            .cloned()
+            .unwrap_or((record.token_id.clone(), Uint128::zero()));
+        // 0.1.0 kept payments in the contract, credit the paid term to its creator so it can
+        // be withdrawn
+        let paid = record.payment_amount.saturating_sub(record.payment_pending);
+        if !paid.is_zero() {
+            credit_balance(
+                storage,
+                &CREATOR_REVENUE,
+                &record.creator,
+                &payment_asset,
+                paid,
+            )?;
+        }
+        let subscription = SubscriptionState {
+            status: record.status(),
+            subscription_id: record.subscription_id,
+            offering_id,
+            creator: record.creator,
+            subscriber: subscriber.clone(),
+            token_id: token_id.clone(),
+            nft_address: nft_address.clone(),
+            start_time: record.start_time,
+            end_time: record.end_time,
+            payment_amount: record.payment_amount,
+            payment_pending: record.payment_pending,
+            payment_asset: payment_asset.clone(),
+            metadata: None,
+            refund_policy: RefundPolicy::NoRefund,
+            subscription_duration: record.subscription_duration,
+            auto_renew: false,
+            max_periods: 1,
+            multi_period_discount: None,
+            amount_paid: record.payment_amount,
+            amount_credited: record.payment_amount,
+            grace_period_seconds: 0,
+            membership_token_id: None,
+        };
+        subscriptions().save(storage, (nft_address, token_id, subscriber), &subscription)?;
+    }
+
+    Ok(migrated)
+}
@@ -8,11 +8,15 @@ use andromeda_std::{
     },
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Timestamp, Uint128};
 use cw20::Cw20ReceiveMsg;
 use cw721::Cw721ReceiveMsg;
+use cw_utils::Expiration;
 
-use crate::state::SubscriptionState;
+use crate::state::{
+    Asset, CouponDiscount, Offering, Payout, RefundPolicy, RevenueShare, SubscriptionState,
+    SubscriptionStatus,
+};
 
 #[andr_instantiate]
 #[cw_serde]
@@ -20,6 +24,9 @@ use crate::state::SubscriptionState;
 pub struct InstantiateMsg {
     pub authorized_cw20_addresses: Option<Vec<AndrAddr>>,
     pub authorized_token_addresses: Option<Vec<AndrAddr>>,
+    /// Andromeda CW721 ADO to mint a membership token to each subscriber from. This contract must
+    /// be its minter, and the CW721 must let the minter burn tokens it does not own.
+    pub membership_nft: Option<AndrAddr>,
 }
 
 #[andr_exec]
@@ -29,8 +36,134 @@ pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     /// Message to handle CW721 NFT transfers.
     ReceiveNft(Cw721ReceiveMsg),
-    /// Cancel an existing subscription.
-    Cancel { nft_address: String },
+    /// Subscribe to an offering, paying with the native funds attached to the message.
+    /// Paying the price of several periods (up to the offering's maximum) extends the first term.
+    Subscribe {
+        /// The NFT token ID of the offering tier to subscribe to
+        token_id: String,
+        /// The NFT contract address that issued the token
+        nft_address: String,
+        /// Coupon code to redeem for a discount on the price
+        coupon: Option<String>,
+    },
+    /// Start the free trial of an offering without payment. Each address can claim an offering's
+    /// trial once, and the first renewal converts it to a paid subscription.
+    StartTrial {
+        /// The NFT token ID of the offering tier to try
+        token_id: String,
+        /// The NFT contract address that issued the token
+        nft_address: String,
+    },
+    /// Renew a subscription, paying with the native funds attached to the message.
+    /// Renewing before the current period ends extends it from its current end time.
+    /// The subscription is selected by its offering's NFT tier or by its ID.
+    Renew {
+        /// The NFT token ID of the offering tier to renew
+        token_id: Option<String>,
+        /// The NFT contract address that issued the token
+        nft_address: Option<String>,
+        /// ID of the subscription to renew, instead of the NFT tier
+        subscription_id: Option<Uint128>,
+        /// Coupon code to redeem for a discount on the price
+        coupon: Option<String>,
+    },
+    /// Cancel an existing subscription, refunding the subscriber according to the offering's refund policy.
+    /// The subscription is selected by its offering's NFT tier or by its ID.
+    Cancel {
+        nft_address: Option<String>,
+        /// The NFT token ID of the offering tier
+        token_id: Option<String>,
+        /// ID of the subscription to cancel, instead of the NFT tier
+        subscription_id: Option<Uint128>,
+        /// Cancel right away, otherwise access is kept until the end of the current period
+        immediate: bool,
+    },
+    /// Opt in or out of automatic renewal. Renewals are pulled from a CW20 allowance the
+    /// subscriber grants to this contract.
+    SetAutoRenew {
+        nft_address: String,
+        /// The NFT token ID of the offering tier
+        token_id: String,
+        enabled: bool,
+    },
+    /// Roll over expired subscriptions, up to `limit` per call. Callable by anyone.
+    /// Payment is debited from the subscriber's prepaid balance first, then pulled from a CW20
+    /// allowance if auto-renewal is enabled. Subscriptions that cannot be paid for are marked past due
+    /// during the offering's grace period and lapsed after it.
+    ProcessRenewals { limit: Option<u32> },
+    /// Deposit the native funds attached to the message into the sender's prepaid balance.
+    Deposit {},
+    /// Withdraw from the sender's prepaid balance.
+    WithdrawBalance {
+        /// The asset to withdraw
+        asset: Asset,
+        /// Amount to withdraw, defaults to the full balance
+        amount: Option<Uint128>,
+    },
+    /// Update the terms of an offering for new subscriptions. Restricted to the offering's creator.
+    UpdateOffering {
+        nft_address: String,
+        /// The NFT token ID of the offering tier
+        token_id: String,
+        payment_amount: Option<Uint128>,
+        duration: Option<u64>,
+        metadata: Option<String>,
+        refund_policy: Option<RefundPolicy>,
+        max_periods: Option<u64>,
+        multi_period_discount: Option<Decimal>,
+        grace_period_seconds: Option<u64>,
+        trial_duration: Option<u64>,
+        transferable: Option<bool>,
+        /// Replaces the revenue recipients, an empty list pays all revenue to the creator
+        revenue_split: Option<Vec<RevenueShare>>,
+        payout: Option<Payout>,
+    },
+    /// Create a coupon code for an offering. Restricted to the offering's creator.
+    /// Only a hash of the code is stored.
+    CreateCoupon {
+        nft_address: String,
+        /// The NFT token ID of the offering tier
+        token_id: String,
+        code: String,
+        discount: CouponDiscount,
+        /// Total number of redemptions allowed, unlimited if not set
+        max_redemptions: Option<u64>,
+        /// Number of redemptions allowed per subscriber, unlimited if not set
+        max_redemptions_per_address: Option<u64>,
+        valid_from: Option<Timestamp>,
+        valid_until: Option<Timestamp>,
+    },
+    /// Delete a coupon code of an offering. Restricted to the offering's creator.
+    DeleteCoupon {
+        nft_address: String,
+        /// The NFT token ID of the offering tier
+        token_id: String,
+        code: String,
+    },
+    /// Delist an offering and return its NFT to the creator. Restricted to the offering's creator.
+    /// Active subscribers keep access until their current period ends but can no longer renew.
+    DelistOffering {
+        nft_address: String,
+        /// The NFT token ID of the offering tier
+        token_id: String,
+    },
+    /// Move the sender's subscription to an offering to `recipient`, keeping its paid time.
+    /// Only allowed for offerings the creator made transferable.
+    TransferSubscription {
+        /// ID of the offering subscribed to
+        offering: Uint128,
+        /// Address to transfer the subscription to
+        recipient: AndrAddr,
+    },
+    /// Withdraw collected subscription revenue. Restricted to the creator owed the revenue.
+    WithdrawRevenue {
+        /// The asset to withdraw
+        asset: Asset,
+        /// Amount to withdraw, defaults to the full claimable balance
+        amount: Option<Uint128>,
+        /// Address to send the revenue to, defaults to the creator
+        recipient: Option<AndrAddr>,
+    },
     /// Restricted to owner.
     AuthorizeContract {
         action: PermissionAction,
@@ -44,45 +177,110 @@ pub enum ExecuteMsg {
     },
 }
 
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Asset that the offerings and subscriptions of contracts upgraded from 0.1.0 are bound to,
+    /// as 0.1.0 accepted any authorized CW20 without recording it. Required for those contracts.
+    pub legacy_payment_asset: Option<Asset>,
+}
+
 #[andr_query]
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(SubscriptionState)]
-    /// Gets the details of a specific subscription using the creator and subscriber composite key.
-    Subscription { creator: String, subscriber: String },
+    /// Gets the details of a specific subscription using the creator, token and subscriber composite key.
+    Subscription {
+        creator: String,
+        token_id: String,
+        subscriber: String,
+    },
+    #[returns(SubscriptionState)]
+    /// Gets the details of a specific subscription using its ID.
+    SubscriptionById { id: Uint128 },
+    #[returns(HasAccessResponse)]
+    /// Checks whether a subscriber has access to an offering, at `at_time` or the current block time.
+    /// `at_time` cannot be in the past. Does not error if the subscriber has no subscription.
+    HasAccess {
+        subscriber: String,
+        /// The offering ID
+        offering: Uint128,
+        at_time: Option<Timestamp>,
+    },
     #[returns(Vec<SubscriptionState>)]
     /// Gets all subscriptions for a specific creator, with optional pagination.
     SubscriptionsForCreator {
         creator: String,
-        start_after: Option<(String, String)>, // Composite key
+        start_after: Option<(String, String, String)>, // Composite key
         limit: Option<u64>,
     },
     #[returns(Vec<SubscriptionState>)]
     /// Gets all subscriptions for a specific subscriber, with optional pagination.
     SubscriptionsForSubscriber {
         subscriber: String,
-        start_after: Option<(String, String)>, // Composite key
+        start_after: Option<(String, String, String)>, // Composite key
         limit: Option<u64>,
     },
     #[returns(Vec<Uint128>)]
     /// Gets all subscription IDs for a specific creator, with optional pagination.
     SubscriptionIdsForCreator {
         creator: String,
-        start_after: Option<(String, String)>, // Composite key
+        start_after: Option<(String, String, String)>, // Composite key
         limit: Option<u64>,
     },
     #[returns(Vec<Uint128>)]
     /// Gets all subscription IDs for a specific subscriber, with optional pagination.
     SubscriptionIdsForSubscriber {
         subscriber: String,
-        start_after: Option<(String, String)>, // Composite key
+        start_after: Option<(String, String, String)>, // Composite key
         limit: Option<u64>,
     },
     #[returns(Vec<Uint128>)]
     /// Gets all active subscription IDs, with optional pagination.
     SubscriptionIdsForActiveSubscriptions {
-        start_after: Option<(String, String)>, // Composite key
+        start_after: Option<(String, String, String)>, // Composite key
+        limit: Option<u64>,
+    },
+    #[returns(Offering)]
+    /// Gets the offering listed for an NFT tier.
+    Offering {
+        nft_address: String,
+        token_id: String,
+    },
+    #[returns(Vec<Offering>)]
+    /// Gets all listed offerings, with optional pagination.
+    Offerings {
+        start_after: Option<(String, String)>, // (nft_address, token_id)
+        limit: Option<u64>,
+    },
+    #[returns(Vec<Offering>)]
+    /// Gets all offerings listed by a specific creator, with optional pagination.
+    OfferingsByCreator {
+        creator: String,
+        start_after: Option<(String, String)>, // (nft_address, token_id)
+        limit: Option<u64>,
+    },
+    #[returns(SimulatePriceResponse)]
+    /// Gets the price a new subscriber pays for `periods` periods (defaults to 1) of an offering,
+    /// with a coupon applied if given.
+    SimulatePrice {
+        /// The offering ID
+        offering: Uint128,
+        coupon: Option<String>,
+        periods: Option<u64>,
+    },
+    #[returns(Vec<AssetBalance>)]
+    /// Gets the claimable revenue balances per token for a specific creator, with optional pagination.
+    ClaimableRevenue {
+        creator: String,
+        start_after: Option<Asset>,
+        limit: Option<u64>,
+    },
+    #[returns(Vec<AssetBalance>)]
+    /// Gets the prepaid balances per token for a specific subscriber, with optional pagination.
+    SubscriberBalances {
+        subscriber: String,
+        start_after: Option<Asset>,
         limit: Option<u64>,
     },
     #[returns(AuthorizedAddressesResponse)]
@@ -95,20 +293,84 @@ pub enum QueryMsg {
     },
 }
 
+#[cw_serde]
+pub struct HasAccessResponse {
+    pub has_access: bool,
+    /// When access ends unless renewed, including any grace period. Not set without access.
+    pub expires_at: Option<Timestamp>,
+    /// Status of the subscription at the checked time, not set if there is no subscription
+    pub status: Option<SubscriptionStatus>,
+}
+
+#[cw_serde]
+pub struct SimulatePriceResponse {
+    /// Amount to pay, including any multi-period and coupon discounts
+    pub price: Uint128,
+    pub asset: Asset,
+}
+
+#[cw_serde]
+pub struct AssetBalance {
+    pub asset: Asset,
+    pub amount: Uint128,
+}
+
+/// Messages sent to the membership CW721, matching the Andromeda CW721 ADO
+#[cw_serde]
+pub enum MembershipNftExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: MembershipTokenExtension,
+    },
+    Burn {
+        token_id: String,
+    },
+}
+
+#[cw_serde]
+pub struct MembershipTokenExtension {
+    pub publisher: String,
+}
+
+/// Metadata of a membership token, stored in its `token_uri` as a JSON data URI
+#[cw_serde]
+pub struct MembershipMetadata {
+    pub subscription_id: Uint128,
+    /// Subscriber the token is held for
+    pub subscriber: String,
+    pub offering_id: Uint128,
+    /// The NFT contract address of the offering tier
+    pub nft_address: String,
+    /// The NFT token ID of the offering tier
+    pub tier: String,
+    /// End of the paid period
+    pub expires_at: Expiration,
+}
+
 #[cw_serde]
 pub enum Cw20HookMsg {
     Subscribe {
-        /// The NFT token ID to associate with this subscription
+        /// The NFT token ID of the offering tier to subscribe to
         token_id: String,
         /// The NFT contract address that issued the token
         nft_address: String,
+        /// Coupon code to redeem for a discount on the price
+        coupon: Option<String>,
     },
     Renew {
-        /// The NFT token ID to associate with this subscription
-        token_id: String,
+        /// The NFT token ID of the offering tier to renew
+        token_id: Option<String>,
         /// The NFT contract address that issued the token
-        nft_address: String,
+        nft_address: Option<String>,
+        /// ID of the subscription to renew, instead of the NFT tier
+        subscription_id: Option<Uint128>,
+        /// Coupon code to redeem for a discount on the price
+        coupon: Option<String>,
     },
+    /// Deposit the sent tokens into the sender's prepaid balance
+    Deposit {},
 }
 
 #[cw_serde]
@@ -116,5 +378,26 @@ pub enum Cw721HookMsg {
     RegisterSubscription {
         duration: u64,
         payment_amount: Uint128,
+        /// The only asset accepted as payment for this offering
+        payment_asset: Asset,
+        /// Optional description of the offering
+        metadata: Option<String>,
+        /// Refund paid out when a subscriber cancels, no refund if not set
+        refund_policy: Option<RefundPolicy>,
+        /// Maximum number of periods a subscriber can pay for in one payment, defaults to 1
+        max_periods: Option<u64>,
+        /// Discount on the total price when paying for more than one period
+        multi_period_discount: Option<Decimal>,
+        /// Time after a period ends during which the subscription is past due but keeps access
+        grace_period_seconds: Option<u64>,
+        /// Length of a free trial each address can claim once with `StartTrial`, none if not set
+        trial_duration: Option<u64>,
+        /// Allow subscribers to transfer their subscription to another address, defaults to false
+        transferable: Option<bool>,
+        /// Recipients to divide the revenue between by weight, all to the creator if not set
+        revenue_split: Option<Vec<RevenueShare>>,
+        /// Forward payments to a recipient as they are made instead of holding them, which
+        /// cannot be combined with a `revenue_split`. Payments are held if not set.
+        payout: Option<Payout>,
     },
 }
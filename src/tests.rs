@@ -1,19 +1,35 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    from_json,
-    testing::{mock_env, mock_info},
-    to_json_binary, Addr, DepsMut, Response, Uint128,
+    coins, from_json,
+    testing::{mock_dependencies, mock_env, mock_info},
+    to_json_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal, Deps, DepsMut, Env, Order,
+    Response, Storage, SystemResult, Timestamp, Uint128, WasmMsg, WasmQuery,
 };
 
 use crate::{
-    contract::{execute, instantiate, query},
-    state::{subscriptions, SubscriptionState},
-    subscription::{Cw20HookMsg, Cw721HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg},
+    contract::{execute, execute_process_renewals, instantiate, query},
+    helpers::SubscriptionContract,
+    state::{
+        coupon_code_hash, migrate_subscriptions, offerings, subscriptions, Asset, CouponDiscount,
+        Offering, OfferingUpdate, Payout, RefundPolicy, RevenueShare, SubscriptionState,
+        SubscriptionStatus, COUPONS,
+    },
+    subscription::{
+        AssetBalance, Cw20HookMsg, Cw721HookMsg, ExecuteMsg, HasAccessResponse, InstantiateMsg,
+        MembershipMetadata, MembershipNftExecuteMsg, MembershipTokenExtension, QueryMsg,
+        SimulatePriceResponse,
+    },
 };
 
 pub use andromeda_std::{
-    ado_base::permissioning::{LocalPermission, Permission},
+    ado_base::{
+        permissioning::{LocalPermission, Permission},
+        rates::{LocalRate, LocalRateType, LocalRateValue, PercentRate, Rate, RatesMessage},
+    },
     ado_contract::ADOContract,
-    amp::AndrAddr,
+    amp::{AndrAddr, Recipient},
     common::{
         context::ExecuteContext,
         denom::{SEND_CW20_ACTION, SEND_NFT_ACTION},
@@ -22,8 +38,9 @@ pub use andromeda_std::{
     testing::mock_querier::{mock_dependencies_custom, MOCK_APP_CONTRACT, MOCK_KERNEL_CONTRACT},
 };
 
-use cw20::Cw20ReceiveMsg;
-use cw721::Cw721ReceiveMsg;
+use cw20::{AllowanceResponse, BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use cw_storage_plus::{Index, IndexList, IndexedMap, MultiIndex, PrefixBound};
 use cw_utils::Expiration;
 
 fn init(
@@ -36,6 +53,7 @@ fn init(
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses,
         authorized_token_addresses,
+        membership_nft: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -65,6 +83,7 @@ fn test_instantiate_with_multiple_authorized_cw20_addresses() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         owner: None,
         authorized_token_addresses: None,
+        membership_nft: None,
         authorized_cw20_addresses: Some(authorized_cw20_addresses.clone()),
     };
 
@@ -107,6 +126,7 @@ fn test_instantiate_with_multiple_authorized_cw721_addresses() {
         owner: None,
         authorized_cw20_addresses: None,
         authorized_token_addresses: Some(authorized_token_addresses.clone()),
+        membership_nft: None,
     };
 
     let res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -141,6 +161,7 @@ fn test_instantiate_with_owner_set() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         owner: Some("new_owner".to_string()),
         authorized_token_addresses: None,
+        membership_nft: None,
         authorized_cw20_addresses: None,
     };
 
@@ -166,6 +187,7 @@ fn test_execute_subscribe_success() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
         authorized_token_addresses: None,
+        membership_nft: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -173,30 +195,35 @@ fn test_execute_subscribe_success() {
 
     // Mock a subscription offering
     let creator = "creator".to_string();
-    let subscriber = "".to_string(); // No subscriber initially
     let token_id = "token_1".to_string();
     let nft_address = cw20_address.clone(); // Use the authorized CW20 address
     let payment_amount = Uint128::from(100u128);
     let duration = 3600;
 
-    let offering = SubscriptionState {
-        subscription_id: Uint128::from(1u128),
+    let offering = Offering {
+        offering_id: Uint128::from(1u128),
         creator: creator.clone(),
-        subscriber: subscriber.clone(),
-        token_id: token_id.clone(),
         nft_address: nft_address.clone(),
-        start_time: Expiration::Never {},
-        end_time: Expiration::Never {},
+        token_id: token_id.clone(),
         payment_amount,
-        payment_pending: payment_amount,
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token(cw20_address.clone()),
         subscription_duration: duration,
-        is_active: false,
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
+        max_periods: 1,
+        multi_period_discount: None,
+        grace_period_seconds: 0,
+        trial_duration: 0,
+        transferable: false,
+        revenue_split: vec![],
+        payout: Payout::Custody,
+        subscription_count: 0,
+        refundable_until: Timestamp::from_nanos(0),
     };
-    subscriptions()
+    offerings()
         .save(
             deps.as_mut().storage,
-            (nft_address.clone(), subscriber.clone()),
+            (nft_address.clone(), token_id.clone()),
             &offering,
         )
         .unwrap();
@@ -208,12 +235,19 @@ fn test_execute_subscribe_success() {
         msg: to_json_binary(&Cw20HookMsg::Subscribe {
             token_id: token_id.clone(),
             nft_address: nft_address.clone(),
+            coupon: None,
         })
         .unwrap(),
     };
 
     let msg = ExecuteMsg::Receive(receive_msg);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        msg,
+    )
+    .unwrap();
 
     // Validate the response
     assert_eq!(res.attributes.len(), 7);
@@ -230,10 +264,10 @@ fn test_execute_subscribe_success() {
     let saved_subscription = subscriptions()
         .load(
             deps.as_ref().storage,
-            (nft_address.clone(), "user".to_string()),
+            (nft_address.clone(), token_id.clone(), "user".to_string()),
         )
         .unwrap();
-    assert!(saved_subscription.is_active);
+    assert_eq!(saved_subscription.status, SubscriptionStatus::Active);
     assert_eq!(
         saved_subscription.start_time,
         Expiration::AtTime(env.block.time)
@@ -242,6 +276,12 @@ fn test_execute_subscribe_success() {
         saved_subscription.end_time,
         Expiration::AtTime(env.block.time.plus_seconds(duration))
     );
+    assert_eq!(saved_subscription.offering_id, offering.offering_id);
+
+    let saved_offering = offerings()
+        .load(deps.as_ref().storage, (nft_address, token_id))
+        .unwrap();
+    assert_eq!(saved_offering.subscription_count, 1);
 }
 
 #[test]
@@ -256,6 +296,7 @@ fn test_execute_renew_success() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
         authorized_token_addresses: None,
+        membership_nft: None,
     };
 
     let owner_info = mock_info("owner", &[]);
@@ -271,6 +312,7 @@ fn test_execute_renew_success() {
 
     let subscription = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::from(1u128),
         creator: creator.clone(),
         subscriber: subscriber.clone(),
         token_id: token_id.clone(),
@@ -279,26 +321,66 @@ fn test_execute_renew_success() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token(cw20_address.clone()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: false,
+        status: SubscriptionStatus::Expired,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::zero(),
+        amount_credited: Uint128::zero(),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (nft_address.clone(), subscriber.clone()),
+            (nft_address.clone(), token_id.clone(), subscriber.clone()),
             &subscription,
         )
         .unwrap();
 
+    // Mock the listed offering the subscription belongs to
+    let offering = Offering {
+        offering_id: subscription.offering_id,
+        creator: creator.clone(),
+        nft_address: nft_address.clone(),
+        token_id: token_id.clone(),
+        payment_amount,
+        payment_asset: subscription.payment_asset.clone(),
+        subscription_duration: duration,
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
+        max_periods: 1,
+        multi_period_discount: None,
+        grace_period_seconds: 0,
+        trial_duration: 0,
+        transferable: false,
+        revenue_split: vec![],
+        payout: Payout::Custody,
+        subscription_count: 1,
+        refundable_until: Timestamp::from_nanos(0),
+    };
+    offerings()
+        .save(
+            deps.as_mut().storage,
+            (nft_address.clone(), token_id.clone()),
+            &offering,
+        )
+        .unwrap();
+
     // Define the Cw20ReceiveMsg for renewal
     let receive_msg = Cw20ReceiveMsg {
         sender: "user".to_string(),
         amount: payment_amount,
         msg: to_json_binary(&Cw20HookMsg::Renew {
-            token_id: token_id.clone(),
-            nft_address: nft_address.clone(),
+            token_id: Some(token_id.clone()),
+            nft_address: Some(nft_address.clone()),
+            subscription_id: None,
+            coupon: None,
         })
         .unwrap(),
     };
@@ -324,11 +406,11 @@ fn test_execute_renew_success() {
     let renewed_subscription = subscriptions()
         .load(
             deps.as_ref().storage,
-            (nft_address.clone(), subscriber.clone()),
+            (nft_address.clone(), token_id.clone(), subscriber.clone()),
         )
         .unwrap();
 
-    assert!(renewed_subscription.is_active);
+    assert_eq!(renewed_subscription.status, SubscriptionStatus::Active);
     assert_eq!(
         renewed_subscription.start_time,
         Expiration::AtTime(env.block.time)
@@ -352,6 +434,7 @@ fn test_execute_receive_cw721_success() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: None,
         authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
     };
 
     let owner_info = mock_info("owner", &[]);
@@ -366,6 +449,16 @@ fn test_execute_receive_cw721_success() {
     let hook_msg = Cw721HookMsg::RegisterSubscription {
         duration,
         payment_amount,
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: None,
+        max_periods: None,
+        multi_period_discount: None,
+        grace_period_seconds: None,
+        trial_duration: None,
+        transferable: None,
+        revenue_split: None,
+        payout: None,
     };
 
     let receive_msg = Cw721ReceiveMsg {
@@ -388,16 +481,17 @@ fn test_execute_receive_cw721_success() {
     assert_eq!(res.attributes[1].value, creator);
 
     // Validate the state
-    let saved_subscription = subscriptions()
+    let saved_offering = offerings()
         .load(
             deps.as_ref().storage,
-            (cw721_address.clone(), "".to_string()),
+            (cw721_address.clone(), token_id.clone()),
         )
         .unwrap();
-    assert!(!saved_subscription.is_active);
-    assert_eq!(saved_subscription.token_id, token_id);
-    assert_eq!(saved_subscription.payment_amount, payment_amount);
-    assert_eq!(saved_subscription.subscription_duration, duration);
+    assert_eq!(saved_offering.creator, creator);
+    assert_eq!(saved_offering.token_id, token_id);
+    assert_eq!(saved_offering.payment_amount, payment_amount);
+    assert_eq!(saved_offering.subscription_duration, duration);
+    assert_eq!(saved_offering.subscription_count, 0);
 }
 #[test]
 fn test_execute_receive_cw721_duplicate_registration() {
@@ -410,6 +504,7 @@ fn test_execute_receive_cw721_duplicate_registration() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: None,
         authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
     };
 
     let owner_info = mock_info("owner", &[]);
@@ -423,6 +518,16 @@ fn test_execute_receive_cw721_duplicate_registration() {
     let hook_msg = Cw721HookMsg::RegisterSubscription {
         duration,
         payment_amount,
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: None,
+        max_periods: None,
+        multi_period_discount: None,
+        grace_period_seconds: None,
+        trial_duration: None,
+        transferable: None,
+        revenue_split: None,
+        payout: None,
     };
 
     let receive_msg = Cw721ReceiveMsg {
@@ -472,6 +577,7 @@ fn test_execute_cancel_success() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: None,
         authorized_token_addresses: None,
+        membership_nft: None,
     };
 
     let owner_info = mock_info("owner", &[]);
@@ -484,6 +590,7 @@ fn test_execute_cancel_success() {
 
     let subscription = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber.clone(),
         token_id: token_id.clone(),
@@ -492,23 +599,35 @@ fn test_execute_cancel_success() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(3600)), // 1 hour later
         payment_amount: Uint128::from(100u128),
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: 3600,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::from(100u128),
+        amount_credited: Uint128::from(100u128),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     // Save the subscription in state
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (nft_address.clone(), subscriber.clone()),
+            (nft_address.clone(), token_id.clone(), subscriber.clone()),
             &subscription,
         )
         .unwrap();
 
     // Define the `Cancel` ExecuteMsg
     let msg = ExecuteMsg::Cancel {
-        nft_address: nft_address.clone(),
+        nft_address: Some(nft_address.clone()),
+        token_id: Some(token_id.clone()),
+        subscription_id: None,
+        immediate: true,
     };
 
     // Execute the cancel operation
@@ -525,13 +644,15 @@ fn test_execute_cancel_success() {
     let cancelled_subscription = subscriptions()
         .load(
             deps.as_ref().storage,
-            (nft_address.clone(), subscriber.clone()),
+            (nft_address.clone(), token_id.clone(), subscriber.clone()),
         )
         .unwrap();
 
-    assert!(!cancelled_subscription.is_active);
-    assert_eq!(cancelled_subscription.start_time, Expiration::Never {});
-    assert_eq!(cancelled_subscription.end_time, Expiration::Never {});
+    assert_eq!(cancelled_subscription.status, SubscriptionStatus::Cancelled);
+    assert_eq!(
+        cancelled_subscription.end_time,
+        Expiration::AtTime(env.block.time)
+    );
     assert_eq!(
         cancelled_subscription.payment_pending,
         subscription.payment_amount
@@ -550,6 +671,7 @@ fn test_execute_cancel_failure_no_subscription() {
         kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
         authorized_cw20_addresses: None,
         authorized_token_addresses: None,
+        membership_nft: None,
     };
 
     let owner_info = mock_info("owner", &[]);
@@ -558,7 +680,10 @@ fn test_execute_cancel_failure_no_subscription() {
     // Define the `Cancel` ExecuteMsg with no subscription in state
     let nft_address = "nft_contract".to_string();
     let msg = ExecuteMsg::Cancel {
-        nft_address: nft_address.clone(),
+        nft_address: Some(nft_address.clone()),
+        token_id: Some("token_1".to_string()),
+        subscription_id: None,
+        immediate: true,
     };
     let err = execute(deps.as_mut(), env.clone(), subscriber_info.clone(), msg).unwrap_err();
     assert_eq!(
@@ -586,6 +711,7 @@ fn test_query_subscription_success() {
 
     let subscription = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber.clone(),
         token_id: token_id.clone(),
@@ -594,21 +720,31 @@ fn test_query_subscription_success() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: payment_amount,
+        amount_credited: payment_amount,
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (creator.clone(), subscriber.clone()),
+            (creator.clone(), token_id.clone(), subscriber.clone()),
             &subscription,
         )
         .unwrap();
 
     let query_msg = QueryMsg::Subscription {
         creator: creator.clone(),
+        token_id: token_id.clone(),
         subscriber: subscriber.clone(),
     };
 
@@ -621,7 +757,7 @@ fn test_query_subscription_success() {
     );
     assert_eq!(queried_subscription.creator, creator);
     assert_eq!(queried_subscription.subscriber, subscriber);
-    assert_eq!(queried_subscription.is_active, true);
+    assert_eq!(queried_subscription.status, SubscriptionStatus::Active);
     assert_eq!(queried_subscription.payment_amount, payment_amount);
     assert_eq!(queried_subscription.start_time, subscription.start_time);
     assert_eq!(queried_subscription.end_time, subscription.end_time);
@@ -642,6 +778,7 @@ fn test_query_subscriptions_for_creator_success() {
 
     let subscription_1 = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber_1.clone(),
         token_id: token_id.clone(),
@@ -650,13 +787,23 @@ fn test_query_subscriptions_for_creator_success() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: payment_amount,
+        amount_credited: payment_amount,
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     let subscription_2 = SubscriptionState {
         subscription_id: Uint128::from(2u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber_2.clone(),
         token_id: token_id.clone(),
@@ -665,15 +812,24 @@ fn test_query_subscriptions_for_creator_success() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: payment_amount,
+        amount_credited: payment_amount,
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (creator.clone(), subscriber_1.clone()),
+            (creator.clone(), token_id.clone(), subscriber_1.clone()),
             &subscription_1,
         )
         .unwrap();
@@ -681,7 +837,7 @@ fn test_query_subscriptions_for_creator_success() {
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (creator.clone(), subscriber_2.clone()),
+            (creator.clone(), token_id.clone(), subscriber_2.clone()),
             &subscription_2,
         )
         .unwrap();
@@ -715,6 +871,7 @@ fn test_query_subscription_ids_for_active_subscriptions() {
 
     let active_subscription = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber_1.clone(),
         token_id: token_id.clone(),
@@ -723,13 +880,23 @@ fn test_query_subscription_ids_for_active_subscriptions() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: payment_amount,
+        amount_credited: payment_amount,
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     let inactive_subscription = SubscriptionState {
         subscription_id: Uint128::from(2u128),
+        offering_id: Uint128::zero(),
         creator: creator.clone(),
         subscriber: subscriber_2.clone(),
         token_id: token_id.clone(),
@@ -738,15 +905,24 @@ fn test_query_subscription_ids_for_active_subscriptions() {
         end_time: Expiration::AtTime(env.block.time.minus_seconds(duration)),
         payment_amount,
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: duration,
-        is_active: false,
+        status: SubscriptionStatus::Expired,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::zero(),
+        amount_credited: Uint128::zero(),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (creator.clone(), subscriber_1.clone()),
+            (creator.clone(), token_id.clone(), subscriber_1.clone()),
             &active_subscription,
         )
         .unwrap();
@@ -754,7 +930,7 @@ fn test_query_subscription_ids_for_active_subscriptions() {
     subscriptions()
         .save(
             deps.as_mut().storage,
-            (creator.clone(), subscriber_2.clone()),
+            (creator.clone(), token_id.clone(), subscriber_2.clone()),
             &inactive_subscription,
         )
         .unwrap();
@@ -779,6 +955,7 @@ fn test_query_active_subscription_ids() {
     // Add two active subscriptions and one expired subscription to the state
     let active_subscription_1 = SubscriptionState {
         subscription_id: Uint128::from(1u128),
+        offering_id: Uint128::zero(),
         creator: "creator_1".to_string(),
         subscriber: "subscriber_1".to_string(),
         token_id: "token_1".to_string(),
@@ -787,13 +964,23 @@ fn test_query_active_subscription_ids() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(100)), // Active
         payment_amount: Uint128::from(100u128),
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: 200,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::from(100u128),
+        amount_credited: Uint128::from(100u128),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     let active_subscription_2 = SubscriptionState {
         subscription_id: Uint128::from(2u128),
+        offering_id: Uint128::zero(),
         creator: "creator_2".to_string(),
         subscriber: "subscriber_2".to_string(),
         token_id: "token_2".to_string(),
@@ -802,13 +989,23 @@ fn test_query_active_subscription_ids() {
         end_time: Expiration::AtTime(env.block.time.plus_seconds(50)), // Active
         payment_amount: Uint128::from(200u128),
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: 250,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::from(200u128),
+        amount_credited: Uint128::from(200u128),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     let expired_subscription = SubscriptionState {
         subscription_id: Uint128::from(3u128),
+        offering_id: Uint128::zero(),
         creator: "creator_3".to_string(),
         subscriber: "subscriber_3".to_string(),
         token_id: "token_3".to_string(),
@@ -817,9 +1014,18 @@ fn test_query_active_subscription_ids() {
         end_time: Expiration::AtTime(env.block.time.minus_seconds(100)), // Expired
         payment_amount: Uint128::from(300u128),
         payment_pending: Uint128::zero(),
-        payment_denom: "CW20".to_string(),
+        payment_asset: Asset::Cw20Token("cw20_contract".to_string()),
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
         subscription_duration: 200,
-        is_active: true,
+        status: SubscriptionStatus::Active,
+        auto_renew: false,
+        max_periods: 1,
+        multi_period_discount: None,
+        amount_paid: Uint128::from(300u128),
+        amount_credited: Uint128::from(300u128),
+        grace_period_seconds: 0,
+        membership_token_id: None,
     };
 
     subscriptions()
@@ -827,6 +1033,7 @@ fn test_query_active_subscription_ids() {
             deps.as_mut().storage,
             (
                 active_subscription_1.nft_address.clone(),
+                active_subscription_1.token_id.clone(),
                 active_subscription_1.subscriber.clone(),
             ),
             &active_subscription_1,
@@ -838,6 +1045,7 @@ fn test_query_active_subscription_ids() {
             deps.as_mut().storage,
             (
                 active_subscription_2.nft_address.clone(),
+                active_subscription_2.token_id.clone(),
                 active_subscription_2.subscriber.clone(),
             ),
             &active_subscription_2,
@@ -849,6 +1057,7 @@ fn test_query_active_subscription_ids() {
             deps.as_mut().storage,
             (
                 expired_subscription.nft_address.clone(),
+                expired_subscription.token_id.clone(),
                 expired_subscription.subscriber.clone(),
             ),
             &expired_subscription,
@@ -869,3 +1078,3932 @@ fn test_query_active_subscription_ids() {
     assert!(active_ids.contains(&Uint128::from(2u128)));
     assert!(!active_ids.contains(&Uint128::from(3u128)));
 }
+
+#[test]
+fn test_query_subscriptions_by_index() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let cw721_address = "authorized_cw721".to_string();
+    init(
+        deps.as_mut(),
+        None,
+        Some(vec![AndrAddr::from_string(&cw721_address)]),
+    );
+
+    for (token_id, grace_period_seconds) in [("basic", 100), ("pro", 0)] {
+        let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "creator".to_string(),
+            token_id: token_id.to_string(),
+            msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+                duration: 1000,
+                payment_amount: Uint128::from(100u128),
+                payment_asset: Asset::NativeToken("uandr".to_string()),
+                metadata: None,
+                refund_policy: None,
+                max_periods: None,
+                multi_period_discount: None,
+                grace_period_seconds: Some(grace_period_seconds),
+                trial_duration: None,
+                transferable: None,
+                revenue_split: None,
+                payout: None,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&cw721_address, &[]),
+            register_msg,
+        )
+        .unwrap();
+    }
+
+    // Subscription IDs 3 to 5 follow the two offering IDs
+    for (subscriber, token_id) in [("user", "basic"), ("user", "pro"), ("other", "basic")] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(subscriber, &coins(100, "uandr")),
+            ExecuteMsg::Subscribe {
+                token_id: token_id.to_string(),
+                nft_address: cw721_address.clone(),
+                coupon: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionsForSubscriber {
+            subscriber: "user".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let user_subscriptions: Vec<SubscriptionState> = from_json(&res).unwrap();
+    assert_eq!(user_subscriptions.len(), 2);
+    assert!(user_subscriptions.iter().all(|s| s.subscriber == "user"));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionIdsForSubscriber {
+            subscriber: "user".to_string(),
+            start_after: Some((
+                cw721_address.clone(),
+                "basic".to_string(),
+                "user".to_string(),
+            )),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let user_ids: Vec<Uint128> = from_json(&res).unwrap();
+    assert_eq!(user_ids, vec![Uint128::from(4u128)]);
+
+    // After the period ends only the subscriptions within their grace period keep access,
+    // ordered by the end of their access and then by key
+    env.block.time = env.block.time.plus_seconds(1050);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionIdsForActiveSubscriptions {
+            start_after: None,
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let first_page: Vec<Uint128> = from_json(&res).unwrap();
+    assert_eq!(first_page, vec![Uint128::from(5u128)]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionIdsForActiveSubscriptions {
+            start_after: Some((
+                cw721_address.clone(),
+                "basic".to_string(),
+                "other".to_string(),
+            )),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let second_page: Vec<Uint128> = from_json(&res).unwrap();
+    assert_eq!(second_page, vec![Uint128::from(3u128)]);
+
+    // A subscription that is no longer stored, for example after a transfer, starts the listing over
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::SubscriptionIdsForActiveSubscriptions {
+            start_after: Some((
+                cw721_address.clone(),
+                "basic".to_string(),
+                "transferred".to_string(),
+            )),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let ids: Vec<Uint128> = from_json(&res).unwrap();
+    assert_eq!(ids, vec![Uint128::from(5u128), Uint128::from(3u128)]);
+}
+
+#[test]
+fn test_withdraw_revenue_after_subscribe() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+    let creator = "creator".to_string();
+    let payment_amount = Uint128::from(100u128);
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Register the offering
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: creator.clone(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 3600,
+            payment_amount,
+            payment_asset: Asset::Cw20Token(cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    // Subscribe, paying with the authorized CW20
+    let subscribe_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: payment_amount,
+        msg: to_json_binary(&Cw20HookMsg::Subscribe {
+            token_id: "token_1".to_string(),
+            nft_address: cw721_address.clone(),
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        subscribe_msg,
+    )
+    .unwrap();
+
+    // The payment is credited to the creator
+    let query_msg = QueryMsg::ClaimableRevenue {
+        creator: creator.clone(),
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap();
+    let balances: Vec<AssetBalance> = from_json(&res).unwrap();
+    assert_eq!(
+        balances,
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(cw20_address.clone()),
+            amount: payment_amount,
+        }]
+    );
+
+    // Withdrawing more than the balance fails
+    let msg = ExecuteMsg::WithdrawRevenue {
+        asset: Asset::Cw20Token(cw20_address.clone()),
+        amount: Some(Uint128::from(101u128)),
+        recipient: None,
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(&creator, &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Insufficient revenue. Available 100, requested 101.".to_string(),
+        }
+    );
+
+    // Withdraw the full balance
+    let msg = ExecuteMsg::WithdrawRevenue {
+        asset: Asset::Cw20Token(cw20_address.clone()),
+        amount: None,
+        recipient: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&creator, &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: creator.clone(),
+                amount: payment_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let balances: Vec<AssetBalance> = from_json(&res).unwrap();
+    assert!(balances.is_empty());
+}
+
+#[test]
+fn test_native_subscribe_and_renew() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+
+    let cw721_address = "authorized_cw721".to_string();
+    let creator = "creator".to_string();
+    let payment_amount = Uint128::from(100u128);
+    let duration = 3600;
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: None,
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Register an offering priced in a native denom
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: creator.clone(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration,
+            payment_amount,
+            payment_asset: Asset::NativeToken("uandr".to_string()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let subscribe_msg = ExecuteMsg::Subscribe {
+        token_id: "token_1".to_string(),
+        nft_address: cw721_address.clone(),
+        coupon: None,
+    };
+
+    // Paying with another denom fails
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &coins(100, "uusd")),
+        subscribe_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Payment(_)));
+
+    // Paying with the wrong amount fails
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &coins(50, "uandr")),
+        subscribe_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment amount. Expected 100, received 50.".to_string(),
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &coins(100, "uandr")),
+        subscribe_msg,
+    )
+    .unwrap();
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (
+                cw721_address.clone(),
+                "token_1".to_string(),
+                "user".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(
+        subscription.payment_asset,
+        Asset::NativeToken("uandr".to_string())
+    );
+
+    // Paying for a native offering with a CW20 fails
+    let cw20_renew_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: payment_amount,
+        msg: to_json_binary(&Cw20HookMsg::Renew {
+            token_id: Some("token_1".to_string()),
+            nft_address: Some(cw721_address.clone()),
+            subscription_id: None,
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        cw20_renew_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment asset. Expected native:uandr, received cw20:owner.".to_string(),
+        }
+    );
+
+    // Renew once the subscription has expired
+    env.block.time = env.block.time.plus_seconds(duration + 1);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &coins(100, "uandr")),
+        ExecuteMsg::Renew {
+            token_id: Some("token_1".to_string()),
+            nft_address: Some(cw721_address.clone()),
+            subscription_id: None,
+            coupon: None,
+        },
+    )
+    .unwrap();
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (
+                cw721_address.clone(),
+                "token_1".to_string(),
+                "user".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(duration))
+    );
+
+    // Both payments are withdrawable by the creator as native funds
+    let asset = Asset::NativeToken("uandr".to_string());
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&creator, &[]),
+        ExecuteMsg::WithdrawRevenue {
+            asset,
+            amount: None,
+            recipient: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: creator,
+            amount: coins(200, "uandr"),
+        })
+    );
+}
+
+#[test]
+fn test_subscribe_rejects_other_authorized_cw20() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+
+    let usdc_address = "usdc_cw20".to_string();
+    let other_address = "other_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+    let payment_amount = Uint128::from(100u128);
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![
+            AndrAddr::from_string(&usdc_address),
+            AndrAddr::from_string(&other_address),
+        ]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Register an offering priced in the USDC CW20
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 3600,
+            payment_amount,
+            payment_asset: Asset::Cw20Token(usdc_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let subscribe_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: payment_amount,
+        msg: to_json_binary(&Cw20HookMsg::Subscribe {
+            token_id: "token_1".to_string(),
+            nft_address: cw721_address.clone(),
+            coupon: None,
+        })
+        .unwrap(),
+    });
+
+    // Another authorized CW20 cannot pay for the offering
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&other_address, &[]),
+        subscribe_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: format!(
+                "Invalid payment asset. Expected cw20:{}, received cw20:{}.",
+                usdc_address, other_address
+            ),
+        }
+    );
+
+    // The offering's own token is accepted
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(&usdc_address, &[]),
+        subscribe_msg,
+    )
+    .unwrap();
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (cw721_address, "token_1".to_string(), "user".to_string()),
+        )
+        .unwrap();
+    assert_eq!(subscription.payment_asset, Asset::Cw20Token(usdc_address));
+}
+
+#[test]
+fn test_multiple_tiers_per_collection() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Register Basic and Pro tiers from the same collection
+    for (token_id, payment_amount, duration) in [("basic", 100u128, 3600), ("pro", 250u128, 7200)] {
+        let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "creator".to_string(),
+            token_id: token_id.to_string(),
+            msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+                duration,
+                payment_amount: Uint128::from(payment_amount),
+                payment_asset: Asset::Cw20Token(cw20_address.clone()),
+                metadata: None,
+                refund_policy: None,
+                max_periods: None,
+                multi_period_discount: None,
+                grace_period_seconds: None,
+                trial_duration: None,
+                transferable: None,
+                revenue_split: None,
+                payout: None,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&cw721_address, &[]),
+            register_msg,
+        )
+        .unwrap();
+    }
+
+    // Paying the Basic price for the Pro tier fails
+    let subscribe_pro = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "user".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_json_binary(&Cw20HookMsg::Subscribe {
+                token_id: "pro".to_string(),
+                nft_address: cw721_address.clone(),
+                coupon: None,
+            })
+            .unwrap(),
+        })
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        subscribe_pro(100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment amount. Expected 250, received 100.".to_string(),
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        subscribe_pro(250),
+    )
+    .unwrap();
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (cw721_address.clone(), "pro".to_string(), "user".to_string()),
+        )
+        .unwrap();
+    assert_eq!(subscription.payment_amount, Uint128::from(250u128));
+    assert_eq!(subscription.subscription_duration, 7200);
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(7200))
+    );
+
+    // The user holds no Basic subscription
+    assert!(subscriptions()
+        .may_load(
+            deps.as_ref().storage,
+            (cw721_address, "basic".to_string(), "user".to_string()),
+        )
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_update_and_delist_offering() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+    let creator = "creator".to_string();
+    let token_id = "token_1".to_string();
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: creator.clone(),
+        token_id: token_id.clone(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 3600,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let update_msg = update_offering_msg(
+        &cw721_address,
+        &token_id,
+        OfferingUpdate {
+            payment_amount: Some(Uint128::from(150u128)),
+            duration: Some(7200),
+            metadata: Some("Monthly plan".to_string()),
+            ..Default::default()
+        },
+    );
+
+    // Only the creator can update the offering
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        update_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&creator, &[]),
+        update_msg,
+    )
+    .unwrap();
+
+    let offering = offerings()
+        .load(
+            deps.as_ref().storage,
+            (cw721_address.clone(), token_id.clone()),
+        )
+        .unwrap();
+    assert_eq!(offering.payment_amount, Uint128::from(150u128));
+    assert_eq!(offering.subscription_duration, 7200);
+    assert_eq!(offering.metadata, Some("Monthly plan".to_string()));
+
+    // Subscribe at the updated price
+    let subscribe_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: Uint128::from(150u128),
+        msg: to_json_binary(&Cw20HookMsg::Subscribe {
+            token_id: token_id.clone(),
+            nft_address: cw721_address.clone(),
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        subscribe_msg,
+    )
+    .unwrap();
+
+    // Delisting returns the NFT to the creator
+    let delist_msg = ExecuteMsg::DelistOffering {
+        nft_address: cw721_address.clone(),
+        token_id: token_id.clone(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        delist_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&creator, &[]),
+        delist_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw721_address.clone(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: creator.clone(),
+                token_id: token_id.clone(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The active subscriber keeps their subscription but can no longer renew
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (cw721_address.clone(), token_id.clone(), "user".to_string()),
+        )
+        .unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+
+    let renew_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: Uint128::from(150u128),
+        msg: to_json_binary(&Cw20HookMsg::Renew {
+            token_id: Some(token_id.clone()),
+            nft_address: Some(cw721_address.clone()),
+            subscription_id: None,
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    let err = execute(deps.as_mut(), env, mock_info(&cw20_address, &[]), renew_msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "This subscription offering has been delisted.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_renewal_after_lapse_uses_current_terms() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    offering.subscribe(deps.as_mut(), env.clone(), "other");
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                payment_amount: Some(Uint128::from(150u128)),
+                duration: Some(2000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // A subscription with access renews on the terms it was sold with
+    env.block.time = env.block.time.plus_seconds(500);
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    assert_eq!(subscription.payment_amount, Uint128::from(100u128));
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(mock_env().block.time.plus_seconds(2000))
+    );
+
+    // A cancelled subscription starts a new term at the current price and duration
+    let err = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "other",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment amount. Expected 150, received 100.".to_string(),
+        }
+    );
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "other",
+            150,
+            offering.renew_hook(),
+        )
+        .unwrap();
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("other"))
+        .unwrap();
+    assert_eq!(subscription.payment_amount, Uint128::from(150u128));
+    assert_eq!(subscription.subscription_duration, 2000);
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(2000))
+    );
+
+    // The same applies once a subscription has expired
+    env.block.time = env.block.time.plus_seconds(2000);
+    offering
+        .pay(deps.as_mut(), env, "user", 150, offering.renew_hook())
+        .unwrap();
+}
+
+#[test]
+fn test_offering_requires_price_and_duration() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                payment_amount: Some(Uint128::zero()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Payment amount must be greater than 0.".to_string(),
+        }
+    );
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_2".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 0,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&offering.cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Subscription duration must be greater than 0.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_query_offerings() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let cw721_address = "authorized_cw721".to_string();
+    init(
+        deps.as_mut(),
+        None,
+        Some(vec![AndrAddr::from_string(&cw721_address)]),
+    );
+
+    for (creator, token_id, payment_amount) in [
+        ("creator", "basic", 100u128),
+        ("creator", "pro", 250u128),
+        ("other_creator", "enterprise", 1000u128),
+    ] {
+        let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: creator.to_string(),
+            token_id: token_id.to_string(),
+            msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+                duration: 1000,
+                payment_amount: Uint128::from(payment_amount),
+                payment_asset: Asset::NativeToken("uandr".to_string()),
+                metadata: None,
+                refund_policy: None,
+                max_periods: None,
+                multi_period_discount: None,
+                grace_period_seconds: None,
+                trial_duration: None,
+                transferable: None,
+                revenue_split: None,
+                payout: None,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&cw721_address, &[]),
+            register_msg,
+        )
+        .unwrap();
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Offering {
+            nft_address: cw721_address.clone(),
+            token_id: "pro".to_string(),
+        },
+    )
+    .unwrap();
+    let offering: Offering = from_json(&res).unwrap();
+    assert_eq!(offering.creator, "creator");
+    assert_eq!(offering.payment_amount, Uint128::from(250u128));
+    assert_eq!(offering.subscription_count, 0);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Offerings {
+            start_after: Some((cw721_address.clone(), "basic".to_string())),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let listed: Vec<Offering> = from_json(&res).unwrap();
+    let token_ids: Vec<String> = listed.into_iter().map(|o| o.token_id).collect();
+    assert_eq!(token_ids, vec!["enterprise", "pro"]);
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::OfferingsByCreator {
+            creator: "creator".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let by_creator: Vec<Offering> = from_json(&res).unwrap();
+    let token_ids: Vec<String> = by_creator.into_iter().map(|o| o.token_id).collect();
+    assert_eq!(token_ids, vec!["basic", "pro"]);
+}
+
+/// Offering of "token_1" of an authorized CW721, paid in an authorized CW20 and registered by
+/// "creator" on a freshly instantiated contract
+struct TestOffering {
+    cw20_address: String,
+    cw721_address: String,
+    token_id: String,
+    offering_id: Uint128,
+}
+
+impl TestOffering {
+    /// Instantiates the contract and registers the offering at 100 per 1000 second period, with
+    /// the terms set in `terms` replacing these and the contract's defaults
+    fn register(mut deps: DepsMut, terms: OfferingUpdate) -> Self {
+        let cw20_address = "authorized_cw20".to_string();
+        let cw721_address = "authorized_cw721".to_string();
+        let token_id = "token_1".to_string();
+        init(
+            deps.branch(),
+            Some(vec![AndrAddr::from_string(&cw20_address)]),
+            Some(vec![AndrAddr::from_string(&cw721_address)]),
+        );
+
+        let OfferingUpdate {
+            payment_amount,
+            duration,
+            metadata,
+            refund_policy,
+            max_periods,
+            multi_period_discount,
+            grace_period_seconds,
+            trial_duration,
+            transferable,
+            revenue_split,
+            payout,
+        } = terms;
+        let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "creator".to_string(),
+            token_id: token_id.clone(),
+            msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+                duration: duration.unwrap_or(1000),
+                payment_amount: payment_amount.unwrap_or(Uint128::from(100u128)),
+                payment_asset: Asset::Cw20Token(cw20_address.clone()),
+                metadata,
+                refund_policy,
+                max_periods,
+                multi_period_discount,
+                grace_period_seconds,
+                trial_duration,
+                transferable,
+                revenue_split,
+                payout,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.branch(),
+            mock_env(),
+            mock_info(&cw721_address, &[]),
+            register_msg,
+        )
+        .unwrap();
+        let offering_id = offerings()
+            .load(deps.storage, (cw721_address.clone(), token_id.clone()))
+            .unwrap()
+            .offering_id;
+
+        TestOffering {
+            cw20_address,
+            cw721_address,
+            token_id,
+            offering_id,
+        }
+    }
+
+    /// Key of `subscriber`'s subscription to the offering
+    fn key(&self, subscriber: &str) -> (String, String, String) {
+        (
+            self.cw721_address.clone(),
+            self.token_id.clone(),
+            subscriber.to_string(),
+        )
+    }
+
+    /// Hook message subscribing to the offering
+    fn subscribe_hook(&self) -> Cw20HookMsg {
+        Cw20HookMsg::Subscribe {
+            token_id: self.token_id.clone(),
+            nft_address: self.cw721_address.clone(),
+            coupon: None,
+        }
+    }
+
+    /// Hook message renewing a subscription to the offering
+    fn renew_hook(&self) -> Cw20HookMsg {
+        Cw20HookMsg::Renew {
+            token_id: Some(self.token_id.clone()),
+            nft_address: Some(self.cw721_address.clone()),
+            subscription_id: None,
+            coupon: None,
+        }
+    }
+
+    /// Sends `amount` of the CW20 from `subscriber` with the hook message `msg`
+    fn pay(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        subscriber: &str,
+        amount: u128,
+        msg: Cw20HookMsg,
+    ) -> Result<Response, ContractError> {
+        execute(
+            deps,
+            env,
+            mock_info(&self.cw20_address, &[]),
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: subscriber.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_json_binary(&msg).unwrap(),
+            }),
+        )
+    }
+
+    /// Subscribes `subscriber` for one period at 100, returning the ID of the subscription
+    fn subscribe(&self, mut deps: DepsMut, env: Env, subscriber: &str) -> Uint128 {
+        self.pay(deps.branch(), env, subscriber, 100, self.subscribe_hook())
+            .unwrap();
+        subscriptions()
+            .load(deps.storage, self.key(subscriber))
+            .unwrap()
+            .subscription_id
+    }
+
+    /// Message updating the offering with `update`
+    fn update_msg(&self, update: OfferingUpdate) -> ExecuteMsg {
+        update_offering_msg(&self.cw721_address, &self.token_id, update)
+    }
+
+    /// Updates the offering as its creator
+    fn update(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        update: OfferingUpdate,
+    ) -> Result<Response, ContractError> {
+        execute(
+            deps,
+            env,
+            mock_info("creator", &[]),
+            self.update_msg(update),
+        )
+    }
+
+    /// Message cancelling a subscription to the offering
+    fn cancel_msg(&self, immediate: bool) -> ExecuteMsg {
+        ExecuteMsg::Cancel {
+            nft_address: Some(self.cw721_address.clone()),
+            token_id: Some(self.token_id.clone()),
+            subscription_id: None,
+            immediate,
+        }
+    }
+}
+
+/// `UpdateOffering` message for the offering of an NFT tier
+fn update_offering_msg(nft_address: &str, token_id: &str, update: OfferingUpdate) -> ExecuteMsg {
+    let OfferingUpdate {
+        payment_amount,
+        duration,
+        metadata,
+        refund_policy,
+        max_periods,
+        multi_period_discount,
+        grace_period_seconds,
+        trial_duration,
+        transferable,
+        revenue_split,
+        payout,
+    } = update;
+    ExecuteMsg::UpdateOffering {
+        nft_address: nft_address.to_string(),
+        token_id: token_id.to_string(),
+        payment_amount,
+        duration,
+        metadata,
+        refund_policy,
+        max_periods,
+        multi_period_discount,
+        grace_period_seconds,
+        trial_duration,
+        transferable,
+        revenue_split,
+        payout,
+    }
+}
+
+/// Revenue `creator` can withdraw
+fn claimable_revenue(deps: Deps, env: Env, creator: &str) -> Vec<AssetBalance> {
+    let res = query(
+        deps,
+        env,
+        QueryMsg::ClaimableRevenue {
+            creator: creator.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    from_json(&res).unwrap()
+}
+
+/// Has the operator take `percent` of every payment made with `action`
+fn set_platform_fee(deps: DepsMut, env: Env, action: &str, percent: u64) {
+    let rate_msg = ExecuteMsg::Rates(RatesMessage::SetRate {
+        action: action.to_string(),
+        rate: Rate::Local(LocalRate {
+            rate_type: LocalRateType::Deductive,
+            recipient: Recipient::from_string("platform"),
+            value: LocalRateValue::Percent(PercentRate {
+                percent: Decimal::percent(percent),
+            }),
+            description: None,
+        }),
+    });
+    execute(deps, env, mock_info("owner", &[]), rate_msg).unwrap();
+}
+
+#[test]
+fn test_subscription_by_id() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    let subscription_id = offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let missing_id = subscription_id + Uint128::one();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionById {
+            id: subscription_id,
+        },
+    )
+    .unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.subscriber, "user");
+    assert_eq!(subscription.token_id, "token_1");
+
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionById { id: missing_id },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: format!("No subscription found with ID {}.", missing_id),
+        }
+    );
+
+    // Renew by ID
+    let renew_hook = Cw20HookMsg::Renew {
+        token_id: None,
+        nft_address: None,
+        subscription_id: Some(subscription_id),
+        coupon: None,
+    };
+    offering
+        .pay(deps.as_mut(), env.clone(), "user", 100, renew_hook)
+        .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionById {
+            id: subscription_id,
+        },
+    )
+    .unwrap();
+    let renewed: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(
+        renewed.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(2000))
+    );
+
+    // Only the subscriber can cancel by ID
+    let cancel_msg = ExecuteMsg::Cancel {
+        nft_address: None,
+        token_id: None,
+        subscription_id: Some(subscription_id),
+        immediate: false,
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        cancel_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        cancel_msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4].value, "cancel_scheduled");
+
+    // The subscription must be selected one way only
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        ExecuteMsg::Cancel {
+            nft_address: Some(offering.cw721_address.clone()),
+            token_id: None,
+            subscription_id: Some(subscription_id),
+            immediate: true,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Provide either a subscription ID or an NFT address and token ID.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_cancel_prorated_refund() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+
+    // Cancel with 400 of the 1000 paid seconds remaining
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(600);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+
+    assert_eq!(res.attributes[5].key, "refund");
+    assert_eq!(res.attributes[5].value, "40");
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offering.cw20_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user".to_string(),
+                amount: Uint128::from(40u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The refund is debited from the creator's revenue
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env, "creator"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address),
+            amount: Uint128::from(60u128),
+        }]
+    );
+}
+
+#[test]
+fn test_cancel_refund_after_revenue_withdrawn() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+
+    // The creator withdraws the payment right away
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ExecuteMsg::WithdrawRevenue {
+            asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            amount: None,
+        },
+    )
+    .unwrap();
+
+    // The refund owed is not silently dropped, the immediate cancellation fails instead
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(600);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "The creator's unclaimed revenue of 0 cannot cover the refund of 40.".to_string(),
+        }
+    );
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+
+    // Cancelling at the end of the period still works
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        offering.cancel_msg(false),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_cancel_prorated_refund_after_platform_fee() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    // The operator takes 10% of every renewal
+    set_platform_fee(deps.as_mut(), env.clone(), "Renew", 10);
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+
+    // Cancel halfway through the 2000 paid seconds. Only the 190 credited to the creator is
+    // prorated, not the 10 paid to the operator
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(1000);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[5].key, "refund");
+    assert_eq!(res.attributes[5].value, "95");
+
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env, "creator"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address),
+            amount: Uint128::from(95u128),
+        }]
+    );
+}
+
+#[test]
+fn test_cancel_cooling_off_refund() {
+    let terms = || OfferingUpdate {
+        refund_policy: Some(RefundPolicy::CoolingOff {
+            window_seconds: 100,
+        }),
+        ..Default::default()
+    };
+
+    // Cancelling inside the window refunds the full payment
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(deps.as_mut(), terms());
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.attributes[5].value, "100");
+
+    // Cancelling after the window refunds nothing
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(deps.as_mut(), terms());
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(101);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.attributes.len(), 5);
+}
+
+#[test]
+fn test_cancel_at_period_end() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    let cancel_msg = offering.cancel_msg(false);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        cancel_msg.clone(),
+    )
+    .unwrap();
+
+    // No refund is paid and access is kept
+    assert!(res.messages.is_empty());
+    assert_eq!(res.attributes[3].value, "true");
+    assert_eq!(res.attributes[4].value, "cancel_scheduled");
+
+    let query_msg = QueryMsg::Subscription {
+        creator: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        subscriber: "user".to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::CancelScheduled);
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(1000))
+    );
+
+    // Scheduling twice fails
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        cancel_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Subscription is already scheduled to cancel at the end of the period."
+                .to_string(),
+        }
+    );
+
+    // Access ends with the paid period
+    env.block.time = env.block.time.plus_seconds(1001);
+    let res = query(deps.as_ref(), env, query_msg).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Cancelled);
+}
+
+#[test]
+fn test_transfer_subscription() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    let subscription_id = offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    let transfer_msg = ExecuteMsg::TransferSubscription {
+        offering: offering.offering_id,
+        recipient: AndrAddr::from_string("new_wallet"),
+    };
+
+    // Offerings are not transferable unless the creator allows it
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Subscriptions to this offering are not transferable.".to_string(),
+        }
+    );
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                transferable: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "subscription_transferred");
+    assert_eq!(res.events[0].attributes[2].value, "user");
+    assert_eq!(res.events[0].attributes[3].value, "new_wallet");
+
+    // The subscription moved with its paid time
+    assert!(!subscriptions().has(deps.as_ref().storage, offering.key("user")));
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("new_wallet"))
+        .unwrap();
+    assert_eq!(subscription.subscriber, "new_wallet");
+    assert_eq!(subscription.subscription_id, subscription_id);
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(1000))
+    );
+
+    // The recipient cannot receive a second subscription to the same offering
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let err = execute(deps.as_mut(), env, mock_info("user", &[]), transfer_msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "new_wallet already has a subscription to this offering.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_free_trial() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+
+    let trial_msg = ExecuteMsg::StartTrial {
+        token_id: offering.token_id.clone(),
+        nft_address: offering.cw721_address.clone(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        trial_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "This offering does not have a free trial.".to_string(),
+        }
+    );
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                trial_duration: Some(300),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // The trial starts without payment
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        trial_msg.clone(),
+    )
+    .unwrap();
+    let query_msg = QueryMsg::Subscription {
+        creator: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        subscriber: "other".to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    let trial_end = env.block.time.plus_seconds(300);
+    assert_eq!(subscription.status, SubscriptionStatus::Trialing);
+    assert_eq!(subscription.end_time, Expiration::AtTime(trial_end));
+    assert_eq!(subscription.amount_paid, Uint128::zero());
+
+    // The first renewal converts it to a paid term starting when the trial ends
+    env.block.time = env.block.time.plus_seconds(100);
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "other",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+    let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(subscription.start_time, Expiration::AtTime(trial_end));
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(trial_end.plus_seconds(1000))
+    );
+    assert_eq!(subscription.amount_paid, Uint128::from(100u128));
+
+    // Cancelling does not free up another trial
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), env, mock_info("other", &[]), trial_msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "The free trial of this offering has already been claimed.".to_string(),
+        }
+    );
+}
+
+fn simulate_price(
+    deps: Deps,
+    env: Env,
+    offering: Uint128,
+    coupon: &str,
+) -> Result<SimulatePriceResponse, ContractError> {
+    let query_msg = QueryMsg::SimulatePrice {
+        offering,
+        coupon: Some(coupon.to_string()),
+        periods: None,
+    };
+    query(deps, env, query_msg).map(|res| from_json(&res).unwrap())
+}
+
+#[test]
+fn test_coupons() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let offering_id = offering.offering_id;
+
+    let create_msg = ExecuteMsg::CreateCoupon {
+        nft_address: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        code: "SAVE20".to_string(),
+        discount: CouponDiscount::Percent(Decimal::percent(20)),
+        max_redemptions: Some(2),
+        max_redemptions_per_address: Some(1),
+        valid_from: None,
+        valid_until: None,
+    };
+
+    // Only the creator can create coupons
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        create_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        create_msg,
+    )
+    .unwrap();
+
+    // Only the hash of the code is stored
+    assert!(COUPONS.has(
+        deps.as_ref().storage,
+        (offering_id.u128(), coupon_code_hash("SAVE20"))
+    ));
+
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering_id, "SAVE20").unwrap(),
+        SimulatePriceResponse {
+            price: Uint128::from(80u128),
+            asset: Asset::Cw20Token(offering.cw20_address.clone()),
+        }
+    );
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering_id, "SAVE50").unwrap_err(),
+        ContractError::CustomError {
+            msg: "Invalid coupon code.".to_string(),
+        }
+    );
+
+    let subscribe_hook = Cw20HookMsg::Subscribe {
+        token_id: offering.token_id.clone(),
+        nft_address: offering.cw721_address.clone(),
+        coupon: Some("SAVE20".to_string()),
+    };
+    let renew_hook = Cw20HookMsg::Renew {
+        token_id: Some(offering.token_id.clone()),
+        nft_address: Some(offering.cw721_address.clone()),
+        subscription_id: None,
+        coupon: Some("SAVE20".to_string()),
+    };
+
+    // The discounted price is accepted
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "other",
+            80,
+            subscribe_hook.clone(),
+        )
+        .unwrap();
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("other"))
+        .unwrap();
+    assert_eq!(subscription.amount_paid, Uint128::from(80u128));
+
+    // Each address can redeem the coupon once
+    let err = offering
+        .pay(deps.as_mut(), env.clone(), "other", 80, renew_hook.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "You have reached the maximum number of redemptions of this coupon.".to_string(),
+        }
+    );
+    offering
+        .pay(deps.as_mut(), env.clone(), "user", 80, renew_hook)
+        .unwrap();
+
+    // And it can only be redeemed twice in total
+    let err = offering
+        .pay(deps.as_mut(), env.clone(), "third", 80, subscribe_hook)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "This coupon has reached its maximum number of redemptions.".to_string(),
+        }
+    );
+
+    // Fixed discounts can only be redeemed within their validity window
+    let create_msg = ExecuteMsg::CreateCoupon {
+        nft_address: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        code: "LATER".to_string(),
+        discount: CouponDiscount::Fixed(Uint128::from(30u128)),
+        max_redemptions: None,
+        max_redemptions_per_address: None,
+        valid_from: Some(env.block.time.plus_seconds(100)),
+        valid_until: Some(env.block.time.plus_seconds(200)),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        create_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering_id, "LATER").unwrap_err(),
+        ContractError::CustomError {
+            msg: "This coupon is not valid at this time.".to_string(),
+        }
+    );
+    env.block.time = env.block.time.plus_seconds(100);
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering_id, "LATER")
+            .unwrap()
+            .price,
+        Uint128::from(70u128)
+    );
+
+    let delete_msg = ExecuteMsg::DeleteCoupon {
+        nft_address: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        code: "LATER".to_string(),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        delete_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering_id, "LATER").unwrap_err(),
+        ContractError::CustomError {
+            msg: "Invalid coupon code.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_fixed_coupon_below_price() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    let create_msg = |amount: u128| ExecuteMsg::CreateCoupon {
+        nft_address: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        code: "FREE".to_string(),
+        discount: CouponDiscount::Fixed(Uint128::from(amount)),
+        max_redemptions: None,
+        max_redemptions_per_address: None,
+        valid_from: None,
+        valid_until: None,
+    };
+
+    // A discount reaching the price would leave nothing to pay
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        create_msg(100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Coupon discount must be a nonzero amount below the price or a percentage below 100%."
+                .to_string(),
+        }
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        create_msg(60),
+    )
+    .unwrap();
+
+    // Lowering the price to the discount stops the coupon from being redeemed
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                payment_amount: Some(Uint128::from(60u128)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let expected_err = ContractError::CustomError {
+        msg: "This coupon's discount is not below the price.".to_string(),
+    };
+    assert_eq!(
+        simulate_price(deps.as_ref(), env.clone(), offering.offering_id, "FREE").unwrap_err(),
+        expected_err
+    );
+    let subscribe_hook = Cw20HookMsg::Subscribe {
+        token_id: offering.token_id.clone(),
+        nft_address: offering.cw721_address.clone(),
+        coupon: Some("FREE".to_string()),
+    };
+    let err = offering
+        .pay(deps.as_mut(), env, "user", 1, subscribe_hook)
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn test_renew_with_platform_fee() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    // The operator takes 10% of every renewal
+    set_platform_fee(deps.as_mut(), env.clone(), "Renew", 10);
+    let res = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+
+    // The fee is paid out and reported, and the creator is credited the rest
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.attributes.len(), 10);
+    assert_eq!(res.attributes[8].key, "fee");
+    assert_eq!(res.attributes[8].value, "10");
+    assert_eq!(res.attributes[9].key, "creator_amount");
+    assert_eq!(res.attributes[9].value, "90");
+
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env, "creator"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address),
+            amount: Uint128::from(190u128),
+        }]
+    );
+}
+
+#[test]
+fn test_revenue_split() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    let split_update = |revenue_split: Vec<RevenueShare>| OfferingUpdate {
+        revenue_split: Some(revenue_split),
+        ..Default::default()
+    };
+    let share = |recipient: &str, weight: u64, forward: bool| RevenueShare {
+        recipient: Recipient::from_string(recipient),
+        weight,
+        forward,
+    };
+
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            split_update(vec![share("collab_a", 0, false)]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Revenue split weights must be between 1 and 1000000.".to_string(),
+        }
+    );
+
+    // Weights are capped so their total cannot overflow
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            split_update(vec![
+                share("collab_a", u64::MAX, false),
+                share("collab_b", 1, false),
+            ]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Revenue split weights must be between 1 and 1000000.".to_string(),
+        }
+    );
+
+    // Shares credited for withdrawal have nothing to send a message with
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            split_update(vec![RevenueShare {
+                recipient: Recipient::new(
+                    AndrAddr::from_string("collab_a"),
+                    Some(to_json_binary(&"hook").unwrap()),
+                ),
+                weight: 1,
+                forward: false,
+            }]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Only forwarded revenue shares can carry a message.".to_string(),
+        }
+    );
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            split_update(vec![
+                share("collab_a", 2, false),
+                share("collab_b", 1, true),
+            ]),
+        )
+        .unwrap();
+
+    let res = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+
+    // The forwarded share is sent right away
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offering.cw20_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "collab_b".to_string(),
+                amount: Uint128::from(33u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The other share is credited with the rounding dust, and the creator keeps earlier revenue
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env.clone(), "collab_a"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            amount: Uint128::from(67u128),
+        }]
+    );
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env.clone(), "creator"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            amount: Uint128::from(100u128),
+        }]
+    );
+    assert!(claimable_revenue(deps.as_ref(), env, "collab_b").is_empty());
+}
+
+#[test]
+fn test_revenue_split_requires_no_refund() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+
+    let split = vec![RevenueShare {
+        recipient: Recipient::from_string("collab_a"),
+        weight: 1,
+        forward: false,
+    }];
+    let expected_err = ContractError::CustomError {
+        msg: "A refund policy cannot be combined with a revenue split.".to_string(),
+    };
+
+    // Refunds are debited from the creator, who no longer receives the revenue
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                revenue_split: Some(split.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                refund_policy: Some(RefundPolicy::NoRefund),
+                revenue_split: Some(split),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env,
+            OfferingUpdate {
+                refund_policy: Some(RefundPolicy::CoolingOff {
+                    window_seconds: 100,
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn test_forwarding_revenue_waits_for_refundable_terms() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let term_end = env.block.time.plus_seconds(1000);
+
+    // The subscriber keeps the prorated refund policy, which is paid from the creator's revenue
+    let forward_update = || OfferingUpdate {
+        refund_policy: Some(RefundPolicy::NoRefund),
+        payout: Some(Payout::Immediate {
+            recipient: Recipient::from_string("creator_wallet"),
+        }),
+        ..Default::default()
+    };
+    let err = offering
+        .update(deps.as_mut(), env.clone(), forward_update())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: format!(
+                "Revenue cannot be forwarded until refundable subscriptions end at {}.",
+                term_end
+            ),
+        }
+    );
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                refund_policy: Some(RefundPolicy::NoRefund),
+                revenue_split: Some(vec![RevenueShare {
+                    recipient: Recipient::from_string("collab_a"),
+                    weight: 1,
+                    forward: false,
+                }]),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(err, ContractError::CustomError { .. }));
+
+    // Renewing extends the refundable term
+    env.block.time = env.block.time.plus_seconds(500);
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+    env.block.time = term_end;
+    offering
+        .update(deps.as_mut(), env.clone(), forward_update())
+        .unwrap_err();
+
+    // Once no term sold can be refunded anymore, payments can be forwarded
+    env.block.time = term_end.plus_seconds(1000);
+    offering
+        .update(deps.as_mut(), env, forward_update())
+        .unwrap();
+}
+
+#[test]
+fn test_immediate_payout() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+
+    let recipient = Recipient::new(
+        AndrAddr::from_string("splitter"),
+        Some(to_json_binary(&"split").unwrap()),
+    );
+
+    // A revenue split requires payments to be held
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                revenue_split: Some(vec![RevenueShare {
+                    recipient: Recipient::from_string("collab_a"),
+                    weight: 1,
+                    forward: false,
+                }]),
+                payout: Some(Payout::Immediate {
+                    recipient: recipient.clone(),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "An immediate payout cannot be combined with a revenue split.".to_string(),
+        }
+    );
+
+    offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                payout: Some(Payout::Immediate { recipient }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let res = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+
+    // The payment is sent on to the recipient with its message
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offering.cw20_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: "splitter".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_json_binary(&"split").unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // Only the payment made before the update is held for the creator
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env, "creator"),
+        vec![AssetBalance {
+            asset: Asset::Cw20Token(offering.cw20_address),
+            amount: Uint128::from(100u128),
+        }]
+    );
+}
+
+#[test]
+fn test_immediate_payout_requires_no_refund() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    let payout = Payout::Immediate {
+        recipient: Recipient::from_string("creator_wallet"),
+    };
+    let expected_err = ContractError::CustomError {
+        msg: "A refund policy cannot be combined with an immediate payout.".to_string(),
+    };
+
+    // Forwarded payments leave nothing in the creator's balance to refund from
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                payout: Some(payout.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_2".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 1000,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            metadata: None,
+            refund_policy: Some(RefundPolicy::CoolingOff {
+                window_seconds: 100,
+            }),
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: Some(payout),
+        })
+        .unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&offering.cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn test_set_auto_renew() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        ExecuteMsg::SetAutoRenew {
+            nft_address: offering.cw721_address.clone(),
+            token_id: offering.token_id.clone(),
+            enabled: true,
+        },
+    )
+    .unwrap();
+
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    assert!(subscription.auto_renew);
+}
+
+#[test]
+fn test_process_renewals() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+
+    // Only `funded_user` granted an allowance to the contract
+    let contract_address = env.contract.address.to_string();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let res = match from_json(msg).unwrap() {
+                Cw20QueryMsg::Allowance { owner, spender } => {
+                    let allowance = if owner == "funded_user" && spender == contract_address {
+                        Uint128::from(1000u128)
+                    } else {
+                        Uint128::zero()
+                    };
+                    to_json_binary(&AllowanceResponse {
+                        allowance,
+                        expires: Expiration::Never {},
+                    })
+                }
+                Cw20QueryMsg::Balance { .. } => to_json_binary(&BalanceResponse {
+                    balance: Uint128::from(1000u128),
+                }),
+                _ => panic!("Unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        }
+        _ => panic!("Unexpected query"),
+    });
+
+    let nft_address = "nft_contract".to_string();
+    let token_id = "token_1".to_string();
+    let cw20_address = "cw20_contract".to_string();
+    let offering = Offering {
+        offering_id: Uint128::from(1u128),
+        creator: "creator".to_string(),
+        nft_address: nft_address.clone(),
+        token_id: token_id.clone(),
+        payment_amount: Uint128::from(100u128),
+        payment_asset: Asset::Cw20Token(cw20_address.clone()),
+        subscription_duration: 3600,
+        metadata: None,
+        refund_policy: RefundPolicy::NoRefund,
+        max_periods: 1,
+        multi_period_discount: None,
+        grace_period_seconds: 0,
+        trial_duration: 0,
+        transferable: false,
+        revenue_split: vec![],
+        payout: Payout::Custody,
+        subscription_count: 2,
+        refundable_until: Timestamp::from_nanos(0),
+    };
+    offerings()
+        .save(
+            deps.as_mut().storage,
+            (nft_address.clone(), token_id.clone()),
+            &offering,
+        )
+        .unwrap();
+
+    for (id, subscriber) in [(2u128, "funded_user"), (3u128, "unfunded_user")] {
+        let subscription = SubscriptionState {
+            subscription_id: Uint128::from(id),
+            offering_id: offering.offering_id,
+            creator: offering.creator.clone(),
+            subscriber: subscriber.to_string(),
+            token_id: token_id.clone(),
+            nft_address: nft_address.clone(),
+            start_time: Expiration::AtTime(env.block.time),
+            end_time: Expiration::AtTime(env.block.time.plus_seconds(3600)),
+            payment_amount: offering.payment_amount,
+            payment_pending: Uint128::zero(),
+            payment_asset: offering.payment_asset.clone(),
+            metadata: None,
+            refund_policy: RefundPolicy::NoRefund,
+            subscription_duration: 3600,
+            status: SubscriptionStatus::Active,
+            auto_renew: true,
+            max_periods: 1,
+            multi_period_discount: None,
+            amount_paid: offering.payment_amount,
+            amount_credited: offering.payment_amount,
+            grace_period_seconds: 0,
+            membership_token_id: None,
+        };
+        subscriptions()
+            .save(
+                deps.as_mut().storage,
+                (
+                    nft_address.clone(),
+                    token_id.clone(),
+                    subscriber.to_string(),
+                ),
+                &subscription,
+            )
+            .unwrap();
+    }
+
+    // Nothing is due before the period ends
+    let ctx = ExecuteContext::new(deps.as_mut(), mock_info("keeper", &[]), env.clone());
+    let res = execute_process_renewals(ctx, None).unwrap();
+    assert!(res.messages.is_empty());
+
+    env.block.time = env.block.time.plus_seconds(3601);
+    let ctx = ExecuteContext::new(deps.as_mut(), mock_info("keeper", &[]), env.clone());
+    let res = execute_process_renewals(ctx, None).unwrap();
+
+    // The funded subscription is renewed by pulling the payment
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "funded_user".to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(res.attributes[1].value, "1");
+    assert_eq!(res.attributes[2].value, "1");
+
+    let renewed = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (
+                nft_address.clone(),
+                token_id.clone(),
+                "funded_user".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(renewed.status, SubscriptionStatus::Active);
+    assert!(renewed.auto_renew);
+    assert_eq!(
+        renewed.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(3600))
+    );
+
+    // The unfunded subscription lapses without failing the batch
+    let lapsed = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (
+                nft_address.clone(),
+                token_id.clone(),
+                "unfunded_user".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(lapsed.status, SubscriptionStatus::Expired);
+    assert!(!lapsed.auto_renew);
+    assert_eq!(lapsed.payment_pending, Uint128::from(100u128));
+
+    // Only the renewed subscription is left for later calls to read
+    let due: Vec<(String, String, String)> = subscriptions()
+        .idx
+        .renewal
+        .prefix_range(
+            deps.as_ref().storage,
+            None,
+            Some(PrefixBound::exclusive(u64::MAX)),
+            Order::Ascending,
+        )
+        .map(|res| res.unwrap().0)
+        .collect();
+    assert_eq!(
+        due,
+        vec![(nft_address, token_id, "funded_user".to_string())]
+    );
+}
+
+#[test]
+fn test_prepaid_balance_renewal() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let cw20_address = offering.cw20_address.clone();
+
+    // Prepay for one and a half periods
+    let res = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            150,
+            Cw20HookMsg::Deposit {},
+        )
+        .unwrap();
+    assert_eq!(res.attributes[4].value, "150");
+
+    // Native deposits are tracked separately
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &coins(20, "uandr")),
+        ExecuteMsg::Deposit {},
+    )
+    .unwrap();
+
+    let balances_query = QueryMsg::SubscriberBalances {
+        subscriber: "user".to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env.clone(), balances_query.clone()).unwrap();
+    let balances: Vec<AssetBalance> = from_json(&res).unwrap();
+    assert_eq!(
+        balances,
+        vec![
+            AssetBalance {
+                asset: Asset::Cw20Token(cw20_address.clone()),
+                amount: Uint128::from(150u128),
+            },
+            AssetBalance {
+                asset: Asset::NativeToken("uandr".to_string()),
+                amount: Uint128::from(20u128),
+            },
+        ]
+    );
+
+    // The first rollover is covered by the prepaid balance
+    env.block.time = env.block.time.plus_seconds(1001);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::ProcessRenewals { limit: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.attributes[1].value, "1");
+    assert_eq!(res.attributes[2].value, "0");
+
+    let subscription_query = QueryMsg::Subscription {
+        creator: offering.cw721_address.clone(),
+        token_id: offering.token_id.clone(),
+        subscriber: "user".to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), subscription_query.clone()).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(subscription.payment_pending, Uint128::zero());
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(1000))
+    );
+
+    let revenue = claimable_revenue(deps.as_ref(), env.clone(), "creator");
+    assert_eq!(revenue[0].amount, Uint128::from(200u128));
+
+    // The remaining balance does not cover the next period, so the amount owed is recorded
+    env.block.time = env.block.time.plus_seconds(1001);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::ProcessRenewals { limit: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "0");
+    assert_eq!(res.attributes[2].value, "1");
+
+    let res = query(deps.as_ref(), env.clone(), subscription_query).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Expired);
+    assert_eq!(subscription.payment_pending, Uint128::from(100u128));
+
+    // The leftover balance can be withdrawn
+    let withdraw_msg = ExecuteMsg::WithdrawBalance {
+        asset: Asset::Cw20Token(cw20_address.clone()),
+        amount: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_address,
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        withdraw_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Cannot withdraw a 0 amount.".to_string(),
+        }
+    );
+
+    let res = query(deps.as_ref(), env, balances_query).unwrap();
+    let balances: Vec<AssetBalance> = from_json(&res).unwrap();
+    assert_eq!(balances.len(), 1);
+}
+
+#[test]
+fn test_multi_period_subscription() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let register_msg = |multi_period_discount: Option<Decimal>| {
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "creator".to_string(),
+            token_id: "token_1".to_string(),
+            msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+                duration: 1000,
+                payment_amount: Uint128::from(100u128),
+                payment_asset: Asset::Cw20Token(cw20_address.clone()),
+                metadata: None,
+                refund_policy: None,
+                max_periods: Some(12),
+                multi_period_discount,
+                grace_period_seconds: None,
+                trial_duration: None,
+                transferable: None,
+                revenue_split: None,
+                payout: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // A discount of 100% or more is rejected
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg(Some(Decimal::one())),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Multi-period discount must be less than 100%.".to_string(),
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg(Some(Decimal::percent(10))),
+    )
+    .unwrap();
+
+    let subscribe = |deps: DepsMut, subscriber: &str, amount: u128| {
+        execute(
+            deps,
+            mock_env(),
+            mock_info(&cw20_address, &[]),
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: subscriber.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_json_binary(&Cw20HookMsg::Subscribe {
+                    token_id: "token_1".to_string(),
+                    nft_address: cw721_address.clone(),
+                    coupon: None,
+                })
+                .unwrap(),
+            }),
+        )
+    };
+
+    // Three periods at a 10% discount
+    subscribe(deps.as_mut(), "user", 270).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Subscription {
+            creator: cw721_address.clone(),
+            token_id: "token_1".to_string(),
+            subscriber: "user".to_string(),
+        },
+    )
+    .unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(env.block.time.plus_seconds(3000))
+    );
+    assert_eq!(subscription.amount_paid, Uint128::from(270u128));
+
+    // A single period is charged the full price
+    subscribe(deps.as_mut(), "user_2", 100).unwrap();
+
+    // Amounts that are not the price of a whole number of periods are rejected
+    let err = subscribe(deps.as_mut(), "user_3", 300).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment amount. Expected the price of 1 to 12 periods at 100 each, received 300."
+                .to_string(),
+        }
+    );
+
+    // As are payments for more than the maximum number of periods
+    let err = subscribe(deps.as_mut(), "user_3", 1170).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFunds { .. }));
+}
+
+#[test]
+fn test_early_renewal_stacks_remaining_period() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            refund_policy: Some(RefundPolicy::Prorated),
+            ..Default::default()
+        },
+    );
+    offering.subscribe(deps.as_mut(), mock_env(), "user");
+    let start = mock_env().block.time;
+
+    // Renew with 600 of the 1000 paid seconds remaining
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(400);
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            100,
+            offering.renew_hook(),
+        )
+        .unwrap();
+
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(subscription.start_time, Expiration::AtTime(start));
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(start.plus_seconds(2000))
+    );
+    assert_eq!(subscription.amount_paid, Uint128::from(200u128));
+
+    // The prorated refund covers the whole stacked term
+    env.block.time = start.plus_seconds(1000);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user", &[]),
+        offering.cancel_msg(true),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[5].value, "100");
+}
+
+#[test]
+fn test_has_access() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(deps.as_mut(), OfferingUpdate::default());
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let end_time = env.block.time.plus_seconds(1000);
+
+    let has_access = |subscriber: &str, offering: Uint128, at_time: Option<Timestamp>| {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::HasAccess {
+                subscriber: subscriber.to_string(),
+                offering,
+                at_time,
+            },
+        )
+        .unwrap();
+        from_json::<HasAccessResponse>(&res).unwrap()
+    };
+    let offering_id = offering.offering_id;
+
+    assert_eq!(
+        has_access("user", offering_id, None),
+        HasAccessResponse {
+            has_access: true,
+            expires_at: Some(end_time),
+            status: Some(SubscriptionStatus::Active),
+        }
+    );
+
+    // Checked after the paid period ends
+    assert_eq!(
+        has_access("user", offering_id, Some(end_time.plus_seconds(1))),
+        HasAccessResponse {
+            has_access: false,
+            expires_at: None,
+            status: Some(SubscriptionStatus::Expired),
+        }
+    );
+
+    // Missing subscriptions and offerings do not error
+    let no_access = HasAccessResponse {
+        has_access: false,
+        expires_at: None,
+        status: None,
+    };
+    assert_eq!(has_access("other", offering_id, None), no_access);
+    assert_eq!(
+        has_access("user", offering_id + Uint128::one(), None),
+        no_access
+    );
+
+    // Past changes of status are not recorded, so past times are rejected
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::HasAccess {
+            subscriber: "user".to_string(),
+            offering: offering_id,
+            at_time: Some(env.block.time.minus_seconds(1)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Access can only be checked at the current block time or later.".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_subscription_contract_messages() {
+    let contract = SubscriptionContract(Addr::unchecked("subscription"));
+
+    // CW20 payments are sent to the contract with the hook message attached
+    let msg = contract
+        .subscribe_cw20("cw20_contract", Uint128::from(100u128), "nft", "token_1")
+        .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20_contract".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: "subscription".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_json_binary(&Cw20HookMsg::Subscribe {
+                    token_id: "token_1".to_string(),
+                    nft_address: "nft".to_string(),
+                    coupon: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let msg = contract
+        .renew_native("nft", "token_1", coins(100, "uandr"))
+        .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "subscription".to_string(),
+            msg: to_json_binary(&ExecuteMsg::Renew {
+                token_id: Some("token_1".to_string()),
+                nft_address: Some("nft".to_string()),
+                subscription_id: None,
+                coupon: None,
+            })
+            .unwrap(),
+            funds: coins(100, "uandr"),
+        })
+    );
+}
+
+#[test]
+fn test_grace_period() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let start = env.block.time;
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+
+    init(
+        deps.as_mut(),
+        Some(vec![AndrAddr::from_string(&cw20_address)]),
+        Some(vec![AndrAddr::from_string(&cw721_address)]),
+    );
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 1000,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: Some(500),
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let pay_msg = |msg: Cw20HookMsg| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "user".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_json_binary(&msg).unwrap(),
+        })
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        pay_msg(Cw20HookMsg::Subscribe {
+            token_id: "token_1".to_string(),
+            nft_address: cw721_address.clone(),
+            coupon: None,
+        }),
+    )
+    .unwrap();
+
+    let subscription_query = QueryMsg::Subscription {
+        creator: cw721_address.clone(),
+        token_id: "token_1".to_string(),
+        subscriber: "user".to_string(),
+    };
+
+    // After the period ends the subscription is past due but keeps access
+    env.block.time = start.plus_seconds(1200);
+    let res = query(deps.as_ref(), env.clone(), subscription_query.clone()).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::PastDue);
+    assert_eq!(subscription.payment_pending, Uint128::from(100u128));
+
+    // The crank records it as past due instead of lapsing it, and skips it until the grace period ends
+    for expected_past_due in ["1", "0"] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::ProcessRenewals { limit: None },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2].value, "0");
+        assert_eq!(res.attributes[3].value, expected_past_due);
+    }
+
+    // Renewing within the grace period is backdated to the original end time
+    env.block.time = start.plus_seconds(1300);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        pay_msg(Cw20HookMsg::Renew {
+            token_id: Some("token_1".to_string()),
+            nft_address: Some(cw721_address),
+            subscription_id: None,
+            coupon: None,
+        }),
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), subscription_query.clone()).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(
+        subscription.end_time,
+        Expiration::AtTime(start.plus_seconds(2000))
+    );
+
+    // Access is revoked once the grace period is over
+    env.block.time = start.plus_seconds(2501);
+    let res = query(deps.as_ref(), env, subscription_query).unwrap();
+    let subscription: SubscriptionState = from_json(&res).unwrap();
+    assert_eq!(subscription.status, SubscriptionStatus::Expired);
+}
+
+#[test]
+fn test_period_lengths_are_bounded() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            grace_period_seconds: Some(365 * 24 * 60 * 60),
+            ..Default::default()
+        },
+    );
+
+    // Adding an oversized grace period to the end of a period would overflow the block time
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                grace_period_seconds: Some(20_000_000_000),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "Grace periods cannot be longer than 31536000 seconds.".to_string(),
+        }
+    );
+
+    let period_err = ContractError::CustomError {
+        msg: "Subscription periods and free trials cannot be longer than 315360000 seconds."
+            .to_string(),
+    };
+    let err = offering
+        .update(
+            deps.as_mut(),
+            env.clone(),
+            OfferingUpdate {
+                duration: Some(315_360_001),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, period_err);
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_2".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 1000,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(offering.cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: Some(u64::MAX),
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&offering.cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, period_err);
+
+    // A record stored with an oversized grace period is treated as past due instead of panicking
+    offering.subscribe(deps.as_mut(), env.clone(), "user");
+    let mut subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    subscription.grace_period_seconds = u64::MAX;
+    subscriptions()
+        .save(deps.as_mut().storage, offering.key("user"), &subscription)
+        .unwrap();
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(1001);
+    assert_eq!(
+        subscription.status_at(env.block.time),
+        SubscriptionStatus::PastDue
+    );
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper", &[]),
+        ExecuteMsg::ProcessRenewals { limit: None },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_membership_tokens() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: Some(AndrAddr::from_string("membership_nft")),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 1000,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: None,
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let mint_msg = |end_time: Timestamp| -> CosmosMsg {
+        let metadata = MembershipMetadata {
+            subscription_id: Uint128::from(2u128),
+            subscriber: "user".to_string(),
+            offering_id: Uint128::from(1u128),
+            nft_address: cw721_address.clone(),
+            tier: "token_1".to_string(),
+            expires_at: Expiration::AtTime(end_time),
+        };
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "membership_nft".to_string(),
+            msg: to_json_binary(&MembershipNftExecuteMsg::Mint {
+                token_id: "2".to_string(),
+                owner: "user".to_string(),
+                token_uri: Some(format!(
+                    "data:application/json;base64,{}",
+                    to_json_binary(&metadata).unwrap().to_base64()
+                )),
+                extension: MembershipTokenExtension {
+                    publisher: env.contract.address.to_string(),
+                },
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    let burn_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "membership_nft".to_string(),
+        msg: to_json_binary(&MembershipNftExecuteMsg::Burn {
+            token_id: "2".to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+
+    // Subscribing mints a membership token to the subscriber
+    let subscribe_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_json_binary(&Cw20HookMsg::Subscribe {
+            token_id: "token_1".to_string(),
+            nft_address: cw721_address.clone(),
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        subscribe_msg,
+    )
+    .unwrap();
+    let first_end = env.block.time.plus_seconds(1000);
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, mint_msg(first_end));
+
+    // Renewing reissues the token with the new expiry
+    env.block.time = env.block.time.plus_seconds(500);
+    let renew_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "user".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_json_binary(&Cw20HookMsg::Renew {
+            token_id: None,
+            nft_address: None,
+            subscription_id: Some(Uint128::from(2u128)),
+            coupon: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        renew_msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[0].msg, burn_msg);
+    assert_eq!(res.messages[1].msg, mint_msg(first_end.plus_seconds(1000)));
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (
+                cw721_address.clone(),
+                "token_1".to_string(),
+                "user".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(subscription.membership_token_id, Some("2".to_string()));
+
+    // Cancelling immediately burns it
+    let cancel_msg = ExecuteMsg::Cancel {
+        nft_address: None,
+        token_id: None,
+        subscription_id: Some(Uint128::from(2u128)),
+        immediate: true,
+    };
+    let res = execute(deps.as_mut(), env, mock_info("user", &[]), cancel_msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, burn_msg);
+
+    let subscription = subscriptions()
+        .load(
+            deps.as_ref().storage,
+            (cw721_address, "token_1".to_string(), "user".to_string()),
+        )
+        .unwrap();
+    assert_eq!(subscription.membership_token_id, None);
+}
+
+/// Membership CW721 that lets the owner of a token or its minter burn it
+struct MockMembershipNft {
+    minter: String,
+    owners: BTreeMap<String, String>,
+}
+
+impl MockMembershipNft {
+    /// Executes the membership token messages of `res`, sent by `sender`
+    fn execute(&mut self, sender: &str, res: &Response) -> Result<(), String> {
+        for msg in res.messages.iter() {
+            let CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) = &msg.msg
+            else {
+                continue;
+            };
+            if contract_addr != "membership_nft" {
+                continue;
+            }
+            match from_json(msg).unwrap() {
+                MembershipNftExecuteMsg::Mint {
+                    token_id, owner, ..
+                } => {
+                    if self.owners.insert(token_id.clone(), owner).is_some() {
+                        return Err(format!("Token {} is already minted", token_id));
+                    }
+                }
+                MembershipNftExecuteMsg::Burn { token_id } => match self.owners.get(&token_id) {
+                    Some(owner) if owner == sender || self.minter == sender => {
+                        self.owners.remove(&token_id);
+                    }
+                    Some(_) => return Err(format!("Unauthorized to burn token {}", token_id)),
+                    None => return Err(format!("Token {} not found", token_id)),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_membership_token_burns() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let mut env = mock_env();
+    let contract_address = env.contract.address.to_string();
+    let cw20_address = "authorized_cw20".to_string();
+    let cw721_address = "authorized_cw721".to_string();
+
+    let msg = InstantiateMsg {
+        owner: None,
+        kernel_address: MOCK_KERNEL_CONTRACT.to_string(),
+        authorized_cw20_addresses: Some(vec![AndrAddr::from_string(&cw20_address)]),
+        authorized_token_addresses: Some(vec![AndrAddr::from_string(&cw721_address)]),
+        membership_nft: Some(AndrAddr::from_string("membership_nft")),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let register_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: "creator".to_string(),
+        token_id: "token_1".to_string(),
+        msg: to_json_binary(&Cw721HookMsg::RegisterSubscription {
+            duration: 1000,
+            payment_amount: Uint128::from(100u128),
+            payment_asset: Asset::Cw20Token(cw20_address.clone()),
+            metadata: None,
+            refund_policy: None,
+            max_periods: None,
+            multi_period_discount: None,
+            grace_period_seconds: None,
+            trial_duration: None,
+            transferable: Some(true),
+            revenue_split: None,
+            payout: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw721_address, &[]),
+        register_msg,
+    )
+    .unwrap();
+
+    let pay_msg = |subscriber: &str, msg: Cw20HookMsg| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: subscriber.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_json_binary(&msg).unwrap(),
+        })
+    };
+    let subscribe_msg = || Cw20HookMsg::Subscribe {
+        token_id: "token_1".to_string(),
+        nft_address: cw721_address.clone(),
+        coupon: None,
+    };
+
+    // Tokens are minted to their subscriber, and this contract burns them as the minter
+    let mut membership_nft = MockMembershipNft {
+        minter: contract_address.clone(),
+        owners: BTreeMap::new(),
+    };
+    for subscriber in ["user", "other"] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&cw20_address, &[]),
+            pay_msg(subscriber, subscribe_msg()),
+        )
+        .unwrap();
+        membership_nft.execute(&contract_address, &res).unwrap();
+    }
+    assert_eq!(membership_nft.owners.get("2"), Some(&"user".to_string()));
+
+    // Renewing and transferring reissue the token
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&cw20_address, &[]),
+        pay_msg(
+            "user",
+            Cw20HookMsg::Renew {
+                token_id: None,
+                nft_address: None,
+                subscription_id: Some(Uint128::from(2u128)),
+                coupon: None,
+            },
+        ),
+    )
+    .unwrap();
+    membership_nft.execute(&contract_address, &res).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::TransferSubscription {
+            offering: Uint128::from(1u128),
+            recipient: AndrAddr::from_string("new_wallet"),
+        },
+    )
+    .unwrap();
+    membership_nft.execute(&contract_address, &res).unwrap();
+    assert_eq!(
+        membership_nft.owners.get("2"),
+        Some(&"new_wallet".to_string())
+    );
+
+    // Cancelling immediately burns the token
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &[]),
+        ExecuteMsg::Cancel {
+            nft_address: None,
+            token_id: None,
+            subscription_id: Some(Uint128::from(3u128)),
+            immediate: true,
+        },
+    )
+    .unwrap();
+    membership_nft.execute(&contract_address, &res).unwrap();
+
+    // A lapse burns the token without failing the batch
+    env.block.time = env.block.time.plus_seconds(2001);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper", &[]),
+        ExecuteMsg::ProcessRenewals { limit: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2].value, "1");
+    membership_nft.execute(&contract_address, &res).unwrap();
+    assert!(membership_nft.owners.is_empty());
+
+    // A burn sent by anyone but the owner or the minter is rejected
+    let burn = Response::new().add_message(WasmMsg::Execute {
+        contract_addr: "membership_nft".to_string(),
+        msg: to_json_binary(&MembershipNftExecuteMsg::Burn {
+            token_id: "2".to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    });
+    membership_nft
+        .owners
+        .insert("2".to_string(), "new_wallet".to_string());
+    assert_eq!(
+        membership_nft.execute("user", &burn),
+        Err("Unauthorized to burn token 2".to_string())
+    );
+}
+
+/// Subscription record as stored by 0.1.0
+#[cw_serde]
+struct BaselineSubscriptionState {
+    subscription_id: Uint128,
+    creator: String,
+    subscriber: String,
+    token_id: String,
+    nft_address: String,
+    start_time: Expiration,
+    end_time: Expiration,
+    payment_amount: Uint128,
+    payment_pending: Uint128,
+    payment_denom: String,
+    subscription_duration: u64,
+    is_active: bool,
+}
+
+struct BaselineSubscriptionIndices<'a> {
+    creator: MultiIndex<'a, String, BaselineSubscriptionState, (String, String)>,
+}
+
+impl IndexList<BaselineSubscriptionState> for BaselineSubscriptionIndices<'_> {
+    fn get_indexes(
+        &'_ self,
+    ) -> Box<dyn Iterator<Item = &'_ dyn Index<BaselineSubscriptionState>> + '_> {
+        let v: Vec<&dyn Index<BaselineSubscriptionState>> = vec![&self.creator];
+        Box::new(v.into_iter())
+    }
+}
+
+#[test]
+fn test_migrate_legacy_subscriptions() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let start = env.block.time;
+
+    // Write records the way 0.1.0 did: keyed by (nft_address, subscriber) with a creator index,
+    // and the offering stored as a subscription without a subscriber
+    let baseline: IndexedMap<(String, String), BaselineSubscriptionState, _> = IndexedMap::new(
+        "subscriptions",
+        BaselineSubscriptionIndices {
+            creator: MultiIndex::new(
+                |_pk, subscription| subscription.creator.clone(),
+                "subscriptions",
+                "creator_index",
+            ),
+        },
+    );
+    let record = BaselineSubscriptionState {
+        subscription_id: Uint128::from(1u128),
+        creator: "creator".to_string(),
+        subscriber: String::new(),
+        token_id: "token_1".to_string(),
+        nft_address: "nft_contract".to_string(),
+        start_time: Expiration::Never {},
+        end_time: Expiration::Never {},
+        payment_amount: Uint128::from(100u128),
+        payment_pending: Uint128::from(100u128),
+        payment_denom: "CW20".to_string(),
+        subscription_duration: 1000,
+        is_active: false,
+    };
+    let records = [
+        (String::new(), record.clone()),
+        (
+            "active".to_string(),
+            BaselineSubscriptionState {
+                subscription_id: Uint128::from(2u128),
+                subscriber: "active".to_string(),
+                // 0.1.0 stored the token ID sent by the subscriber
+                token_id: "any".to_string(),
+                start_time: Expiration::AtTime(start),
+                end_time: Expiration::AtTime(start.plus_seconds(1000)),
+                payment_pending: Uint128::zero(),
+                is_active: true,
+                ..record.clone()
+            },
+        ),
+        (
+            "cancelled".to_string(),
+            BaselineSubscriptionState {
+                subscription_id: Uint128::from(3u128),
+                subscriber: "cancelled".to_string(),
+                ..record.clone()
+            },
+        ),
+        (
+            "lapsed".to_string(),
+            BaselineSubscriptionState {
+                subscription_id: Uint128::from(4u128),
+                subscriber: "lapsed".to_string(),
+                start_time: Expiration::AtTime(start.minus_seconds(2000)),
+                end_time: Expiration::AtTime(start.minus_seconds(1000)),
+                ..record
+            },
+        ),
+    ];
+    for (subscriber, record) in records {
+        baseline
+            .save(
+                deps.as_mut().storage,
+                ("nft_contract".to_string(), subscriber),
+                &record,
+            )
+            .unwrap();
+    }
+
+    // A payment asset is required as 0.1.0 did not record it
+    let err = migrate_subscriptions(deps.as_mut().storage, None).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CustomError {
+            msg: "A legacy payment asset is required to migrate the records stored by 0.1.0."
+                .to_string(),
+        }
+    );
+
+    let payment_asset = Asset::Cw20Token("cw20_contract".to_string());
+    assert_eq!(
+        migrate_subscriptions(deps.as_mut().storage, Some(payment_asset.clone())).unwrap(),
+        4
+    );
+
+    // The offering moves to its own map, keyed by its NFT tier
+    let offering = offerings()
+        .load(
+            deps.as_ref().storage,
+            ("nft_contract".to_string(), "token_1".to_string()),
+        )
+        .unwrap();
+    assert_eq!(offering.offering_id, Uint128::from(1u128));
+    assert_eq!(offering.payment_asset, payment_asset);
+    assert_eq!(offering.refund_policy, RefundPolicy::NoRefund);
+    assert_eq!(offering.subscription_count, 3);
+
+    // Subscriptions are re-keyed under the offering's token with a status
+    for (subscriber, status) in [
+        ("active", SubscriptionStatus::Active),
+        ("cancelled", SubscriptionStatus::Cancelled),
+        ("lapsed", SubscriptionStatus::Expired),
+    ] {
+        let migrated = subscriptions()
+            .load(
+                deps.as_ref().storage,
+                (
+                    "nft_contract".to_string(),
+                    "token_1".to_string(),
+                    subscriber.to_string(),
+                ),
+            )
+            .unwrap();
+        assert_eq!(migrated.status, status);
+        assert_eq!(migrated.offering_id, offering.offering_id);
+        assert_eq!(migrated.token_id, "token_1");
+        assert_eq!(migrated.payment_asset, payment_asset);
+    }
+
+    // The creator index only lists the re-keyed subscriptions
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SubscriptionIdsForCreator {
+            creator: "creator".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let ids: Vec<Uint128> = from_json(&res).unwrap();
+    assert_eq!(
+        ids,
+        vec![
+            Uint128::from(2u128),
+            Uint128::from(3u128),
+            Uint128::from(4u128)
+        ]
+    );
+
+    // Only the paid term of the active subscription was still held for the creator
+    assert_eq!(
+        claimable_revenue(deps.as_ref(), env, "creator"),
+        vec![AssetBalance {
+            asset: payment_asset,
+            amount: Uint128::from(100u128),
+        }]
+    );
+}
+
+#[test]
+fn test_prepaid_periods_are_bounded() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let offering = TestOffering::register(
+        deps.as_mut(),
+        OfferingUpdate {
+            max_periods: Some(100_000),
+            ..Default::default()
+        },
+    );
+
+    // The period count is found without pricing every count below it
+    offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "user",
+            9_999_900,
+            offering.subscribe_hook(),
+        )
+        .unwrap();
+    let subscription = subscriptions()
+        .load(deps.as_ref().storage, offering.key("user"))
+        .unwrap();
+    assert_eq!(
+        subscription.end_time,
+        env.block.time.plus_seconds(99_999 * 1000)
+    );
+
+    // Amounts between the prices of two period counts are still rejected
+    let err = offering
+        .pay(
+            deps.as_mut(),
+            env.clone(),
+            "other_user",
+            150,
+            offering.subscribe_hook(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidFunds {
+            msg: "Invalid payment amount. Expected the price of 1 to 100000 periods at 100 each, received 150."
+                .to_string(),
+        }
+    );
+
+    // Paying for every period up front cannot run past the block time
+    let prepaid_err = ContractError::CustomError {
+        msg: "Max periods cannot pay for more than 315360000 seconds up front.".to_string(),
+    };
+    for max_periods in [315_361, u64::MAX] {
+        let err = offering
+            .update(
+                deps.as_mut(),
+                env.clone(),
+                OfferingUpdate {
+                    max_periods: Some(max_periods),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err, prepaid_err);
+    }
+    offering
+        .update(
+            deps.as_mut(),
+            env,
+            OfferingUpdate {
+                max_periods: Some(315_360),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+}